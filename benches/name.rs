@@ -0,0 +1,19 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rxfetch::components::name::{current_uid, PwuId, SystemName};
+
+fn passwd(c: &mut Criterion) {
+    let uid = current_uid();
+    c.bench_function("PwuId::get_alloc", |b| {
+        b.iter(|| PwuId::get_alloc(black_box(uid)))
+    });
+    c.bench_function("PwuId::try_get [u8; 1024]", |b| {
+        b.iter(|| PwuId::try_get([0u8; 1024], black_box(uid)).ok())
+    });
+}
+
+fn uname(c: &mut Criterion) {
+    c.bench_function("SystemName::get", |b| b.iter(SystemName::get));
+}
+
+criterion_group!(benches, passwd, uname);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rxfetch::pci::{gpus_fast, PciAutoIter, PciClass, PciDevIterBackend};
+
+fn gpus(c: &mut Criterion) {
+    c.bench_function("PciAutoIter::filter_class(DISPLAY)", |b| {
+        b.iter(|| {
+            PciAutoIter::try_init()
+                .map(|devices| devices.filter_class(PciClass::DISPLAY).count_ok())
+        })
+    });
+    c.bench_function("gpus_fast", |b| {
+        b.iter(|| gpus_fast().map(|gpus| gpus.len()))
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn procfs_gpus(c: &mut Criterion) {
+    use rxfetch::pci::linux_procfs::ProcBusBackend;
+    // The GPU filter only needs the class, vendor and device
+    let gpus = |devices: ProcBusBackend| {
+        devices
+            .filter_class(PciClass::DISPLAY)
+            .flatten()
+            .map(|mut gpu| (gpu.vendor().ok(), gpu.device().ok()))
+            .count()
+    };
+    c.bench_function("ProcBusBackend GPUs, eager", |b| {
+        b.iter(|| ProcBusBackend::try_init().map(gpus))
+    });
+    c.bench_function("ProcBusBackend GPUs, lazy", |b| {
+        b.iter(|| ProcBusBackend::try_init().map(|devices| gpus(devices.lazy())))
+    });
+}
+
+#[cfg(not(target_os = "linux"))]
+fn procfs_gpus(_: &mut Criterion) {}
+
+criterion_group!(benches, gpus, procfs_gpus);
+criterion_main!(benches);
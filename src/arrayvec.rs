@@ -0,0 +1,527 @@
+//! A fixed-capacity vector stored inline, used to avoid heap allocation for small buffers.
+use crate::display::DisplayBytes;
+use std::{
+    cmp::Ordering,
+    fmt,
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::{Deref, DerefMut},
+    ptr,
+    str::Utf8Error,
+};
+
+pub struct ArrayVec<T, const CAP: usize> {
+    data: [MaybeUninit<T>; CAP],
+    len: usize,
+}
+
+impl<T, const CAP: usize> ArrayVec<T, CAP> {
+    pub const CAPACITY: usize = CAP;
+
+    pub const fn new() -> Self {
+        Self {
+            data: [const { MaybeUninit::uninit() }; CAP],
+            len: 0,
+        }
+    }
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub const fn is_full(&self) -> bool {
+        self.len == CAP
+    }
+    pub const fn capacity(&self) -> usize {
+        CAP
+    }
+    pub const fn remaining_capacity(&self) -> usize {
+        CAP - self.len
+    }
+    /// Sets the length of the vector.
+    ///
+    /// # Safety
+    /// `len` must not exceed `CAP` and the first `len` elements must be initialized.
+    pub unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(len <= CAP);
+        self.len = len;
+    }
+    /// The uninitialized tail of the buffer.
+    /// Use [`ArrayVec::set_len`] to commit elements written into it.
+    pub fn vacant(&mut self) -> &mut [MaybeUninit<T>] {
+        &mut self.data[self.len..]
+    }
+    /// Appends `f(index)` for each vacant index in turn, until `f` returns `None` or the vector is full.
+    ///
+    /// A safe alternative to writing into [`ArrayVec::vacant`] and committing with
+    /// [`ArrayVec::set_len`]. Each element is committed as soon as it's written.
+    pub fn fill_from(&mut self, mut f: impl FnMut(usize) -> Option<T>) {
+        while !self.is_full() {
+            let Some(value) = f(self.len) else {
+                return;
+            };
+            self.data[self.len].write(value);
+            self.len += 1;
+        }
+    }
+    /// Appends an element, handing it back if the vector is full.
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.data[self.len].write(value);
+        self.len += 1;
+        Ok(())
+    }
+    /// Appends an element.
+    ///
+    /// # Panics
+    /// Panics if the vector is full.
+    pub fn push(&mut self, value: T) {
+        if self.try_push(value).is_err() {
+            panic!("ArrayVec::push on a full vector (capacity {CAP})")
+        }
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: the element at `len` was initialized and is no longer tracked by `len`
+        Some(unsafe { self.data[self.len].assume_init_read() })
+    }
+    /// Removes and returns the element at `index`, shifting everything after it to the left.
+    ///
+    /// # Panics
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len;
+        assert!(
+            index < len,
+            "removal index {index} out of bounds (len {len})"
+        );
+        // SAFETY: index is in bounds, the tail is shifted down over the read element
+        unsafe {
+            let base = self.data.as_mut_ptr().cast::<T>();
+            let value = ptr::read(base.add(index));
+            ptr::copy(base.add(index + 1), base.add(index), len - index - 1);
+            self.len -= 1;
+            value
+        }
+    }
+    /// Shortens the vector to `len` elements, dropping the rest.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let old_len = self.len;
+        // Update len first so a panicking Drop can't cause a double drop
+        self.len = len;
+        // SAFETY: elements in len..old_len are initialized and no longer tracked
+        unsafe {
+            let tail = ptr::slice_from_raw_parts_mut(
+                self.data.as_mut_ptr().add(len).cast::<T>(),
+                old_len - len,
+            );
+            ptr::drop_in_place(tail);
+        }
+    }
+    /// Moves the elements from `at` onwards into a new vector, leaving `self` with the first `at`.
+    ///
+    /// # Panics
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let len = self.len;
+        assert!(at <= len, "split index {at} out of bounds (len {len})");
+        let mut tail = Self::new();
+        // SAFETY: elements in at..len are initialized, they're moved into the start of `tail`
+        // and stop being tracked by `self`, so each is owned exactly once
+        unsafe {
+            ptr::copy_nonoverlapping(self.data.as_ptr().add(at), tail.data.as_mut_ptr(), len - at);
+            self.len = at;
+            tail.len = len - at;
+        }
+        tail
+    }
+    /// Grows the vector to `new_len` with values returned by `f`, or truncates it to `new_len`.
+    ///
+    /// # Panics
+    /// Panics if `new_len > CAP`, rather than silently stopping at the capacity.
+    pub fn resize_with(&mut self, new_len: usize, mut f: impl FnMut() -> T) {
+        assert!(
+            new_len <= CAP,
+            "resize length {new_len} exceeds the capacity ({CAP})"
+        );
+        self.truncate(new_len);
+        while self.len < new_len {
+            // Pushing one at a time keeps `len` accurate if `f` panics
+            self.push(f());
+        }
+    }
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` elements are initialized
+        unsafe { std::slice::from_raw_parts(self.data.as_ptr().cast(), self.len) }
+    }
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: the first `len` elements are initialized
+        unsafe { std::slice::from_raw_parts_mut(self.data.as_mut_ptr().cast(), self.len) }
+    }
+    /// Sorts the initialized elements with `cmp`, see [`slice::sort_unstable_by`].
+    pub fn sort_unstable_by(&mut self, cmp: impl FnMut(&T, &T) -> Ordering) {
+        self.as_mut_slice().sort_unstable_by(cmp)
+    }
+    /// Searches the initialized elements, which must be sorted consistently with `f`,
+    /// see [`slice::binary_search_by`].
+    ///
+    /// `Err` holds the index where a matching element could be inserted, at most [`Self::len`].
+    pub fn binary_search_by(&self, f: impl FnMut(&T) -> Ordering) -> Result<usize, usize> {
+        self.as_slice().binary_search_by(f)
+    }
+}
+
+impl<T: Copy, const CAP: usize> ArrayVec<T, CAP> {
+    /// Copies as much of `src` as fits into the spare capacity, returning the number of elements copied.
+    pub fn copy_from_slice(&mut self, src: &[T]) -> usize {
+        let count = src.len().min(self.remaining_capacity());
+        // SAFETY: `count` elements fit in the vacant tail, which can't overlap `src`
+        unsafe {
+            ptr::copy_nonoverlapping(
+                src.as_ptr(),
+                self.data.as_mut_ptr().add(self.len).cast::<T>(),
+                count,
+            );
+        }
+        self.len += count;
+        count
+    }
+    /// Like [`Self::copy_from_slice`], but copies nothing unless all of `src` fits.
+    pub fn try_copy_from_slice(&mut self, src: &[T]) -> Result<(), CapacityError> {
+        if src.len() > self.remaining_capacity() {
+            return Err(CapacityError);
+        }
+        self.copy_from_slice(src);
+        Ok(())
+    }
+}
+
+/// Views of byte buffers, such as attributes read from sysfs, as text.
+impl<const CAP: usize> ArrayVec<u8, CAP> {
+    /// The bytes as a `&str`, without copying.
+    pub fn as_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self)
+    }
+    /// Displays the bytes with invalid UTF-8 replaced, see [`DisplayBytes`].
+    pub fn as_str_lossy(&self) -> DisplayBytes<&[u8]> {
+        DisplayBytes(self)
+    }
+}
+
+impl<T, const CAP: usize> Drop for ArrayVec<T, CAP> {
+    fn drop(&mut self) {
+        self.clear()
+    }
+}
+
+impl<T, const CAP: usize> Default for ArrayVec<T, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const CAP: usize> Clone for ArrayVec<T, CAP> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T, const CAP: usize> Deref for ArrayVec<T, CAP> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const CAP: usize> DerefMut for ArrayVec<T, CAP> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, const CAP: usize> AsRef<[T]> for ArrayVec<T, CAP> {
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const CAP: usize> AsMut<[T]> for ArrayVec<T, CAP> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: fmt::Debug, const CAP: usize> fmt::Debug for ArrayVec<T, CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const CAP: usize> PartialEq for ArrayVec<T, CAP> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq, const CAP: usize> Eq for ArrayVec<T, CAP> {}
+
+/// Collects up to `CAP` elements, ignoring the rest.
+impl<T, const CAP: usize> FromIterator<T> for ArrayVec<T, CAP> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        for value in iter.into_iter().take(CAP) {
+            vec.push(value);
+        }
+        vec
+    }
+}
+
+/// Writes as many bytes as fit, returning `Ok(0)` once full.
+impl<const CAP: usize> std::io::Write for ArrayVec<u8, CAP> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Ok(self.copy_from_slice(buf))
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Owning iterator over the elements of an [`ArrayVec`].
+///
+/// Elements are moved out as they're yielded. Dropping the iterator before it's exhausted,
+/// e.g. after `.take(n)`, drops the remaining elements in order, so every element is dropped exactly once.
+pub struct ArrayVecIter<T, const CAP: usize> {
+    data: [MaybeUninit<T>; CAP],
+    start: usize,
+    end: usize,
+}
+
+impl<T, const CAP: usize> Iterator for ArrayVecIter<T, CAP> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        let idx = self.start;
+        self.start += 1;
+        // SAFETY: elements in start..end are initialized and each is read exactly once
+        Some(unsafe { self.data[idx].assume_init_read() })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl<T, const CAP: usize> DoubleEndedIterator for ArrayVecIter<T, CAP> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        // SAFETY: see `next`
+        Some(unsafe { self.data[self.end].assume_init_read() })
+    }
+}
+
+impl<T, const CAP: usize> ExactSizeIterator for ArrayVecIter<T, CAP> {}
+
+impl<T, const CAP: usize> Drop for ArrayVecIter<T, CAP> {
+    fn drop(&mut self) {
+        // Drop the elements that weren't yielded
+        self.for_each(drop)
+    }
+}
+
+impl<T, const CAP: usize> IntoIterator for ArrayVec<T, CAP> {
+    type Item = T;
+    type IntoIter = ArrayVecIter<T, CAP>;
+    fn into_iter(self) -> Self::IntoIter {
+        let this = ManuallyDrop::new(self);
+        ArrayVecIter {
+            // SAFETY: `this` is never dropped, so ownership of the elements moves to the iterator
+            data: unsafe { ptr::read(&this.data) },
+            start: 0,
+            end: this.len,
+        }
+    }
+}
+
+impl<'a, T, const CAP: usize> IntoIterator for &'a ArrayVec<T, CAP> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const CAP: usize> IntoIterator for &'a mut ArrayVec<T, CAP> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// The operation would exceed the capacity of an [`ArrayVec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError;
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("insufficient capacity")
+    }
+}
+
+impl std::error::Error for CapacityError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::Cell, rc::Rc};
+
+    /// Counts how many times it's been dropped.
+    struct DropCounter(Rc<Cell<usize>>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn split_off_moves_once() {
+        let drops = Rc::new(Cell::new(0));
+        let mut head: ArrayVec<_, 8> = (0..5).map(|_| DropCounter(drops.clone())).collect();
+        let tail = head.split_off(2);
+        assert_eq!((head.len(), tail.len()), (2, 3));
+        assert_eq!(drops.get(), 0);
+        drop(tail);
+        assert_eq!(drops.get(), 3);
+        drop(head);
+        assert_eq!(drops.get(), 5);
+
+        let mut values: ArrayVec<u32, 4> = [1, 2, 3].into_iter().collect();
+        assert!(values.split_off(3).is_empty());
+        assert_eq!(values.split_off(0).as_slice(), [1, 2, 3]);
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn partially_consumed_iter_drops_rest() {
+        let drops = Rc::new(Cell::new(0));
+        let values: ArrayVec<_, 8> = (0..8).map(|_| DropCounter(drops.clone())).collect();
+        let mut iter = values.into_iter();
+        iter.by_ref().take(4).for_each(drop);
+        assert_eq!(drops.get(), 4);
+        assert_eq!(iter.len(), 4);
+        drop(iter);
+        assert_eq!(drops.get(), 8);
+    }
+
+    #[test]
+    fn copy_wide_elements() {
+        let mut values = ArrayVec::<u32, 4>::new();
+        values.push(u32::MAX);
+        // Only three of these fit, the count passed to the copy is in elements rather than bytes
+        assert_eq!(values.copy_from_slice(&[0x1234_5678, 2, 3, 4]), 3);
+        assert_eq!(values.as_slice(), [u32::MAX, 0x1234_5678, 2, 3]);
+        assert_eq!(values.copy_from_slice(&[5]), 0);
+        assert_eq!(values.try_copy_from_slice(&[5]), Err(CapacityError));
+        assert_eq!(values.try_copy_from_slice(&[]), Ok(()));
+
+        let mut wide = ArrayVec::<[u64; 2], 2>::new();
+        assert_eq!(wide.copy_from_slice(&[[1, 2], [3, 4], [5, 6]]), 2);
+        assert_eq!(wide.as_slice(), [[1, 2], [3, 4]]);
+    }
+
+    #[test]
+    fn fill_from() {
+        let mut values = ArrayVec::<u32, 8>::new();
+        values.push(100);
+        let mut calls = Vec::new();
+        values.fill_from(|idx| {
+            calls.push(idx);
+            (idx < 4).then_some(idx as u32 * 10)
+        });
+        assert_eq!(values.as_slice(), [100, 10, 20, 30]);
+        // The call returning None committed nothing
+        assert_eq!(calls, [1, 2, 3, 4]);
+
+        let drops = Rc::new(Cell::new(0));
+        let mut full = ArrayVec::<_, 3>::new();
+        full.fill_from(|_| Some(DropCounter(drops.clone())));
+        assert_eq!(full.len(), 3);
+        full.fill_from(|_| unreachable!());
+        drop(full);
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn resize_with() {
+        let drops = Rc::new(Cell::new(0));
+        let mut created = 0;
+        let mut values = ArrayVec::<_, 8>::new();
+        values.resize_with(6, || {
+            created += 1;
+            DropCounter(drops.clone())
+        });
+        assert_eq!((values.len(), created), (6, 6));
+        values.resize_with(2, || unreachable!());
+        assert_eq!((values.len(), drops.get()), (2, 4));
+        values.resize_with(2, || unreachable!());
+        assert_eq!(drops.get(), 4);
+        drop(values);
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn resize_past_capacity() {
+        let mut values = ArrayVec::<u8, 4>::new();
+        values.resize_with(5, || 0);
+    }
+
+    #[test]
+    fn sort_and_search() {
+        let mut values: ArrayVec<u32, 8> = [31, 2, 30, 10].into_iter().collect();
+        values.sort_unstable_by(u32::cmp);
+        assert_eq!(values.as_slice(), [2, 10, 30, 31]);
+        assert_eq!(values.binary_search_by(|value| value.cmp(&30)), Ok(2));
+        // Past the last element, not into the spare capacity
+        assert_eq!(values.binary_search_by(|value| value.cmp(&32)), Err(4));
+    }
+
+    #[test]
+    fn text() {
+        let mut buf = ArrayVec::<u8, 16>::new();
+        buf.copy_from_slice("amdgpu æ".as_bytes());
+        let text = buf.as_str().unwrap();
+        assert_eq!(text, "amdgpu æ");
+        // Borrowed, not copied
+        assert_eq!(text.as_ptr(), buf.as_ptr());
+        assert_eq!(buf.as_str_lossy().to_string(), "amdgpu æ");
+
+        buf.truncate(buf.len() - 1);
+        assert_eq!(buf.as_str().unwrap_err().valid_up_to(), 7);
+        assert_eq!(buf.as_str_lossy().to_string(), "amdgpu \u{fffd}");
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_off_out_of_bounds() {
+        let mut values: ArrayVec<u32, 4> = [1, 2].into_iter().collect();
+        values.split_off(3);
+    }
+}
@@ -0,0 +1,293 @@
+//! Human-readable byte sizes and counts.
+use std::{env, fmt};
+
+/// How digits are grouped and which decimal mark is used when displaying numbers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberFormat {
+    /// No separators and a `.` decimal mark, for machine-readable output.
+    #[default]
+    Raw,
+    /// `1,234,567.8`
+    Comma,
+    /// `1.234.567,8`
+    Period,
+    /// `1 234 567,8`
+    Space,
+}
+
+impl NumberFormat {
+    /// Picks a format from the numeric locale, following the POSIX precedence of
+    /// `LC_ALL`, `LC_NUMERIC` and `LANG`. Unknown locales, `C` and `POSIX` use [`NumberFormat::Raw`].
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .into_iter()
+            .filter_map(env::var_os)
+            .find(|locale| !locale.is_empty())
+            .map(|locale| Self::from_locale(locale.as_encoded_bytes()))
+            .unwrap_or_default()
+    }
+    /// Picks a format from a locale name such as `de_DE.UTF-8`.
+    pub fn from_locale(locale: &[u8]) -> Self {
+        let language = locale
+            .split(|&b| matches!(b, b'_' | b'.' | b'@'))
+            .next()
+            .unwrap_or_default();
+        match language {
+            b"en" | b"ja" | b"ko" | b"zh" | b"th" | b"he" | b"hi" => NumberFormat::Comma,
+            b"de" | b"es" | b"it" | b"nl" | b"pt" | b"da" | b"id" | b"tr" | b"el" => {
+                NumberFormat::Period
+            }
+            b"fr" | b"ru" | b"pl" | b"cs" | b"sk" | b"sv" | b"fi" | b"nb" | b"uk" | b"hu" => {
+                NumberFormat::Space
+            }
+            _ => NumberFormat::Raw,
+        }
+    }
+    fn separator(self) -> Option<char> {
+        match self {
+            NumberFormat::Raw => None,
+            NumberFormat::Comma => Some(','),
+            NumberFormat::Period => Some('.'),
+            NumberFormat::Space => Some(' '),
+        }
+    }
+    fn decimal_mark(self) -> char {
+        match self {
+            NumberFormat::Raw | NumberFormat::Comma => '.',
+            NumberFormat::Period | NumberFormat::Space => ',',
+        }
+    }
+    /// Displays `value` with thousands separators.
+    pub fn count(self, value: u64) -> Count {
+        Count {
+            value,
+            format: self,
+        }
+    }
+}
+
+/// Created by [`NumberFormat::count`].
+#[derive(Debug, Clone, Copy)]
+pub struct Count {
+    value: u64,
+    format: NumberFormat,
+}
+
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(separator) = self.format.separator() else {
+            return write!(f, "{}", self.value);
+        };
+        // u64::MAX has 20 digits
+        let mut digits = [0u8; 20];
+        let mut len = 0;
+        let mut value = self.value;
+        loop {
+            digits[len] = b'0' + (value % 10) as u8;
+            len += 1;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        for idx in (0..len).rev() {
+            fmt::Write::write_char(f, digits[idx] as char)?;
+            if idx != 0 && idx % 3 == 0 {
+                fmt::Write::write_char(f, separator)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How the binary units of a [`ByteSize`] are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnitStyle {
+    /// `15.6 GiB`
+    Short,
+    /// `15.6G`, as `ls -h` and `du -h` write it.
+    #[default]
+    Abbrev,
+    /// `15.6 gibibytes`
+    Long,
+}
+
+impl UnitStyle {
+    /// The name of the unit at `idx` in [`ByteSize::UNITS`], and whether it's separated
+    /// from the value by a space.
+    fn unit(self, idx: usize, value: u64) -> (&'static str, bool) {
+        const ABBREV: [&str; 7] = ["B", "K", "M", "G", "T", "P", "E"];
+        const LONG: [&str; 7] = [
+            "bytes",
+            "kibibytes",
+            "mebibytes",
+            "gibibytes",
+            "tebibytes",
+            "pebibytes",
+            "exbibytes",
+        ];
+        match self {
+            UnitStyle::Short => (ByteSize::UNITS[idx], true),
+            UnitStyle::Abbrev => (ABBREV[idx], false),
+            UnitStyle::Long if idx == 0 && value == 1 => ("byte", true),
+            UnitStyle::Long => (LONG[idx], true),
+        }
+    }
+}
+
+/// Displays a number of bytes using binary units, e.g. `15.6G`, see [`UnitStyle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    const UNITS: [&'static str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+    /// Displays the size using the separators and decimal mark of `format`.
+    pub fn with_format(self, format: NumberFormat) -> FormattedByteSize {
+        FormattedByteSize {
+            size: self,
+            format,
+            style: UnitStyle::default(),
+        }
+    }
+    /// Displays the size with its unit written in `style`.
+    pub fn with_style(self, style: UnitStyle) -> FormattedByteSize {
+        self.with_format(NumberFormat::default()).with_style(style)
+    }
+    /// The value scaled to the largest unit it reaches, with that unit's index.
+    fn scaled(self) -> (f64, usize) {
+        let mut value = self.0 as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < Self::UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        (value, unit)
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with_format(NumberFormat::Raw).fmt(f)
+    }
+}
+
+/// Created by [`ByteSize::with_format`] or [`ByteSize::with_style`].
+#[derive(Debug, Clone, Copy)]
+pub struct FormattedByteSize {
+    size: ByteSize,
+    format: NumberFormat,
+    style: UnitStyle,
+}
+
+impl FormattedByteSize {
+    pub fn with_format(mut self, format: NumberFormat) -> Self {
+        self.format = format;
+        self
+    }
+    pub fn with_style(mut self, style: UnitStyle) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+impl fmt::Display for FormattedByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (value, mut unit) = self.size.scaled();
+        // Round to tenths once, so e.g. 1023.96 carries into the integer part
+        let mut tenths = (value * 10.0).round() as u64;
+        // And into the next unit when it rounds up to 1024.0
+        if unit != 0 && tenths >= 10240 && unit < ByteSize::UNITS.len() - 1 {
+            unit += 1;
+            tenths = (value / 1024.0 * 10.0).round() as u64;
+        }
+        let (name, spaced) = self.style.unit(unit, self.size.0);
+        let space = if spaced { " " } else { "" };
+        if unit == 0 {
+            return write!(f, "{}{space}{name}", self.format.count(self.size.0));
+        }
+        write!(
+            f,
+            "{}{}{}{space}{name}",
+            self.format.count(tenths / 10),
+            self.format.decimal_mark(),
+            tenths % 10,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts() {
+        assert_eq!(NumberFormat::Raw.count(1234567).to_string(), "1234567");
+        assert_eq!(NumberFormat::Comma.count(1234567).to_string(), "1,234,567");
+        assert_eq!(NumberFormat::Period.count(123456).to_string(), "123.456");
+        assert_eq!(NumberFormat::Space.count(1234).to_string(), "1 234");
+        assert_eq!(NumberFormat::Comma.count(999).to_string(), "999");
+        assert_eq!(NumberFormat::Comma.count(0).to_string(), "0");
+    }
+
+    #[test]
+    fn locales() {
+        assert_eq!(
+            NumberFormat::from_locale(b"en_US.UTF-8"),
+            NumberFormat::Comma
+        );
+        assert_eq!(
+            NumberFormat::from_locale(b"de_DE.UTF-8"),
+            NumberFormat::Period
+        );
+        assert_eq!(
+            NumberFormat::from_locale(b"fr_FR@euro"),
+            NumberFormat::Space
+        );
+        assert_eq!(NumberFormat::from_locale(b"C.UTF-8"), NumberFormat::Raw);
+        assert_eq!(NumberFormat::from_locale(b"POSIX"), NumberFormat::Raw);
+    }
+
+    #[test]
+    fn sizes() {
+        let size = ByteSize(1536 * 1024 * 1024);
+        assert_eq!(size.to_string(), "1.5G");
+        assert_eq!(
+            size.with_format(NumberFormat::Period)
+                .with_style(UnitStyle::Short)
+                .to_string(),
+            "1,5 GiB"
+        );
+        assert_eq!(
+            ByteSize(4096).with_format(NumberFormat::Space).to_string(),
+            "4,0K"
+        );
+        assert_eq!(
+            ByteSize(1000).with_format(NumberFormat::Comma).to_string(),
+            "1,000B"
+        );
+    }
+
+    #[test]
+    fn unit_styles() {
+        let cases = [
+            (1, ["1 B", "1B", "1 byte"]),
+            (1023, ["1023 B", "1023B", "1023 bytes"]),
+            (1024, ["1.0 KiB", "1.0K", "1.0 kibibytes"]),
+            // Rounds up to 1024.0K, which carries into the next unit
+            (1024 * 1024 - 1, ["1.0 MiB", "1.0M", "1.0 mebibytes"]),
+            (
+                1024 * 1024 - 103,
+                ["1023.9 KiB", "1023.9K", "1023.9 kibibytes"],
+            ),
+            (1024 * 1024, ["1.0 MiB", "1.0M", "1.0 mebibytes"]),
+            (5 << 29, ["2.5 GiB", "2.5G", "2.5 gibibytes"]),
+        ];
+        for (bytes, expected) in cases {
+            let styles = [UnitStyle::Short, UnitStyle::Abbrev, UnitStyle::Long];
+            for (style, expected) in styles.into_iter().zip(expected) {
+                assert_eq!(ByteSize(bytes).with_style(style).to_string(), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Sound cards, from the ALSA card list in `/proc/asound/cards`.
+use crate::{display::DisplayBytes, parse::key_values};
+use std::fs;
+
+const ASOUND_CARDS: &str = "/proc/asound/cards";
+
+/// The name of the card ALSA uses by default, e.g. `HDA Intel PCH`.
+///
+/// That's the card with the lowest index, as long as no `asound.conf` picks another.
+/// `None` without ALSA, e.g. on headless servers, or without any cards.
+pub fn default_card() -> Option<DisplayBytes<Vec<u8>>> {
+    let cards = fs::read(ASOUND_CARDS).ok()?;
+    let name = cards_in(&cards).min_by_key(|&(index, _)| index)?.1;
+    Some(DisplayBytes(name.to_vec()))
+}
+
+/// The `(index, name)` of each card. Every card takes two lines:
+///
+/// ```text
+///  0 [PCH            ]: HDA-Intel - HDA Intel PCH
+///                       HDA Intel PCH at 0xf7f10000 irq 32
+/// ```
+fn cards_in(cards: &[u8]) -> impl Iterator<Item = (u32, &[u8])> {
+    key_values(cards, b':').filter_map(|(key, value)| {
+        let (index, id) = key.split_at(key.iter().position(|&b| b == b' ')?);
+        // The second line of a card may contain a `:` too, but never starts with the index
+        id.trim_ascii_start().strip_prefix(b"[")?;
+        let index = std::str::from_utf8(index).ok()?.parse().ok()?;
+        // `driver - name`
+        let name = match value.windows(3).position(|sep| sep == b" - ") {
+            Some(sep) => &value[sep + 3..],
+            None => value,
+        };
+        Some((index, name.trim_ascii()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cards() {
+        let cards = b" 0 [PCH            ]: HDA-Intel - HDA Intel PCH\n\
+            \x20                     HDA Intel PCH at 0xf7f10000 irq 32\n\
+            \x201 [NVidia         ]: HDA-Intel - HDA NVidia\n\
+            \x20                     HDA NVidia at 0xf7080000 irq 17\n\
+            10 [Dock           ]: USB-Audio - USB Audio: Dock\n\
+            \x20                     Lenovo at usb-0000:00:14.0-1, high speed\n";
+        let parsed: Vec<_> = cards_in(cards).collect();
+        assert_eq!(
+            parsed,
+            [
+                (0, &b"HDA Intel PCH"[..]),
+                (1, &b"HDA NVidia"[..]),
+                (10, &b"USB Audio: Dock"[..]),
+            ]
+        );
+        assert_eq!(cards_in(b"--- no soundcards ---\n").count(), 0);
+    }
+}
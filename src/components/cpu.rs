@@ -0,0 +1,751 @@
+//! Processor information from `/proc/cpuinfo`.
+use crate::{
+    display::DisplayBytes,
+    parse::{key_values, sysfs_decimal, sysfs_hex, trim_text},
+};
+use std::{
+    borrow::Cow,
+    fmt, fs, io,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+const CPUINFO: &str = "/proc/cpuinfo";
+const DEV_KVM: &str = "/dev/kvm";
+const PROC_STAT: &str = "/proc/stat";
+const CPU_SYSFS: &str = "/sys/devices/system/cpu";
+const DEVICE_TREE_MODEL: &str = "/proc/device-tree/model";
+
+/// Names of common ARM cores by `(CPU implementer, CPU part)`.
+const ARM_CORES: &[(u32, u32, &str)] = &[
+    (0x41, 0xd03, "ARM Cortex-A53"),
+    (0x41, 0xd04, "ARM Cortex-A35"),
+    (0x41, 0xd05, "ARM Cortex-A55"),
+    (0x41, 0xd07, "ARM Cortex-A57"),
+    (0x41, 0xd08, "ARM Cortex-A72"),
+    (0x41, 0xd09, "ARM Cortex-A73"),
+    (0x41, 0xd0a, "ARM Cortex-A75"),
+    (0x41, 0xd0b, "ARM Cortex-A76"),
+    (0x41, 0xd0c, "ARM Neoverse-N1"),
+    (0x41, 0xd0d, "ARM Cortex-A77"),
+    (0x41, 0xd40, "ARM Neoverse-V1"),
+    (0x41, 0xd41, "ARM Cortex-A78"),
+    (0x41, 0xd44, "ARM Cortex-X1"),
+    (0x41, 0xd46, "ARM Cortex-A510"),
+    (0x41, 0xd47, "ARM Cortex-A710"),
+    (0x41, 0xd48, "ARM Cortex-X2"),
+    (0x41, 0xd49, "ARM Neoverse-N2"),
+    (0x41, 0xd4d, "ARM Cortex-A715"),
+    (0x41, 0xd4e, "ARM Cortex-X3"),
+    (0x41, 0xd80, "ARM Cortex-A520"),
+    (0x41, 0xd81, "ARM Cortex-A720"),
+    (0x41, 0xd82, "ARM Cortex-X4"),
+    (0x51, 0x802, "Qualcomm Kryo 385 Gold"),
+    (0x51, 0x803, "Qualcomm Kryo 385 Silver"),
+    (0x51, 0x804, "Qualcomm Kryo 485 Gold"),
+    (0x51, 0x805, "Qualcomm Kryo 485 Silver"),
+    (0x61, 0x022, "Apple M1 Icestorm"),
+    (0x61, 0x023, "Apple M1 Firestorm"),
+    (0x61, 0x024, "Apple M1 Pro Icestorm"),
+    (0x61, 0x025, "Apple M1 Pro Firestorm"),
+    (0x61, 0x028, "Apple M1 Max Icestorm"),
+    (0x61, 0x029, "Apple M1 Max Firestorm"),
+    (0x61, 0x032, "Apple M2 Blizzard"),
+    (0x61, 0x033, "Apple M2 Avalanche"),
+];
+
+/// Shows the parsed fields rather than the raw file.
+///
+/// The model name comes from the file alone, without falling back to the device tree.
+impl fmt::Debug for CpuInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CpuInfo")
+            .field("model_name", &self.model_name_or(|| None))
+            .field("vendor", &self.vendor())
+            .field("signature", &self.signature())
+            .field("threads", &self.threads())
+            .finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CpuInfo {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("CpuInfo", 4)?;
+        state.serialize_field(
+            "model_name",
+            &self.model_name_or(|| None).map(|model| model.to_string()),
+        )?;
+        state.serialize_field("vendor", &self.vendor())?;
+        state.serialize_field("signature", &self.signature())?;
+        state.serialize_field("threads", &self.threads())?;
+        state.end()
+    }
+}
+
+/// The designer of the processor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CpuVendor {
+    Intel,
+    Amd,
+    /// Cores designed by Arm itself, such as the Cortex and Neoverse lines.
+    Arm,
+    /// Any other vendor, including other designers of ARM cores like Apple or Qualcomm.
+    Other,
+}
+
+/// Whether the processor can run hardware-accelerated VMs, see [`CpuInfo::virtualization`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum VirtSupport {
+    /// VT-x or AMD-V is present and usable.
+    Supported,
+    /// VT-x or AMD-V is present, but `/dev/kvm` is missing.
+    ///
+    /// Usually because it's disabled in firmware, but also when the KVM module isn't loaded.
+    Disabled,
+    /// Neither is present, or firmware hid them from the kernel.
+    Unsupported,
+}
+
+impl fmt::Display for VirtSupport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VirtSupport::Supported => "supported",
+            VirtSupport::Disabled => "disabled",
+            VirtSupport::Unsupported => "unsupported",
+        })
+    }
+}
+
+/// The x86 `cpu family`, `model` and `stepping`, which identify the microarchitecture,
+/// e.g. family 25 is AMD's Zen 3 and Zen 4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CpuSignature {
+    pub family: u32,
+    pub model: u32,
+    pub stepping: u32,
+}
+
+/// The contents of `/proc/cpuinfo`, retained so fields are only parsed when asked for.
+#[derive(Clone)]
+pub struct CpuInfo {
+    buf: Vec<u8>,
+}
+
+impl CpuInfo {
+    pub fn read() -> io::Result<Self> {
+        Ok(Self::from_bytes(fs::read(CPUINFO)?))
+    }
+    pub fn from_bytes(buf: Vec<u8>) -> Self {
+        Self { buf }
+    }
+    /// The fields describing the first processor.
+    fn first_processor(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        let end = self
+            .buf
+            .windows(2)
+            .position(|pair| pair == b"\n\n")
+            .unwrap_or(self.buf.len());
+        key_values(&self.buf[..end], b':')
+    }
+    /// The value of `key` for the first processor.
+    pub fn field(&self, key: &str) -> Option<&[u8]> {
+        self.first_processor()
+            .find(|&(k, _)| k == key.as_bytes())
+            .map(|(_, value)| value)
+    }
+    /// The marketing name, e.g. `AMD Ryzen 9 7950X 16-Core Processor`.
+    ///
+    /// ARM systems have no `model name`, for those this falls back to the board model from the
+    /// device tree, then to the core named by the `CPU implementer` and `CPU part` codes,
+    /// and finally to the raw codes, e.g. `0x41:0xd0b`.
+    pub fn model_name(&self) -> Option<DisplayBytes<Cow<'_, [u8]>>> {
+        self.model_name_or(|| fs::read(DEVICE_TREE_MODEL).ok())
+    }
+    fn model_name_or(
+        &self,
+        device_tree: impl FnOnce() -> Option<Vec<u8>>,
+    ) -> Option<DisplayBytes<Cow<'_, [u8]>>> {
+        if let Some(model) = self.field("model name") {
+            return Some(DisplayBytes(Cow::Borrowed(model)));
+        }
+        if let Some(mut model) = device_tree() {
+            // Device tree strings are NUL terminated
+            let len = model.iter().position(|&b| b == 0).unwrap_or(model.len());
+            model.truncate(len);
+            if !model.trim_ascii().is_empty() {
+                return Some(DisplayBytes(Cow::Owned(model)));
+            }
+        }
+        let implementer = self.field("CPU implementer")?;
+        let part = self.field("CPU part")?;
+        if let (Some(implementer), Some(part)) = (sysfs_hex(implementer, 2), sysfs_hex(part, 3)) {
+            if let Some(&(_, _, name)) = ARM_CORES
+                .iter()
+                .find(|&&(i, p, _)| (i, p) == (implementer, part))
+            {
+                return Some(DisplayBytes(Cow::Borrowed(name.as_bytes())));
+            }
+        }
+        Some(DisplayBytes(Cow::Owned([implementer, part].join(&b':'))))
+    }
+    /// The vendor from `vendor_id` on x86, or from the `CPU implementer` code on ARM.
+    pub fn vendor(&self) -> CpuVendor {
+        if let Some(vendor) = self.field("vendor_id") {
+            return match vendor {
+                b"GenuineIntel" => CpuVendor::Intel,
+                b"AuthenticAMD" => CpuVendor::Amd,
+                _ => CpuVendor::Other,
+            };
+        }
+        match self
+            .field("CPU implementer")
+            .and_then(|code| sysfs_hex(code, 2))
+        {
+            Some(0x41) => CpuVendor::Arm,
+            _ => CpuVendor::Other,
+        }
+    }
+    /// The family, model and stepping numbers, `None` on architectures that don't report them.
+    pub fn signature(&self) -> Option<CpuSignature> {
+        let number = |key| sysfs_decimal(self.field(key)?);
+        Some(CpuSignature {
+            family: number("cpu family")?,
+            model: number("model")?,
+            stepping: number("stepping")?,
+        })
+    }
+    /// The number of logical processors.
+    pub fn threads(&self) -> usize {
+        key_values(&self.buf, b':')
+            .filter(|&(key, _)| key == b"processor")
+            .count()
+    }
+    /// The feature flags of the first processor, e.g. `sse4_2` or `avx2`.
+    ///
+    /// Reads the `flags` line on x86 and the `Features` line on ARM.
+    pub fn flags(&self) -> impl Iterator<Item = &str> {
+        self.field("flags")
+            .or_else(|| self.field("Features"))
+            .unwrap_or_default()
+            .split(u8::is_ascii_whitespace)
+            .filter(|flag| !flag.is_empty())
+            .filter_map(|flag| std::str::from_utf8(flag).ok())
+    }
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags().any(|f| f == flag)
+    }
+    /// Whether the AVX-512 foundation instructions are supported.
+    pub fn has_avx512(&self) -> bool {
+        self.has_flag("avx512f")
+    }
+    /// Whether hardware AES instructions are supported.
+    pub fn has_aes(&self) -> bool {
+        self.has_flag("aes")
+    }
+    /// Whether VT-x (`vmx`) or AMD-V (`svm`) is supported, and enabled as far as can be told.
+    ///
+    /// The kernel only creates `/dev/kvm` when KVM can use them, so that tells the two apart.
+    pub fn virtualization(&self) -> VirtSupport {
+        self.virtualization_with(Path::new(DEV_KVM))
+    }
+    fn virtualization_with(&self, kvm: &Path) -> VirtSupport {
+        if !self.has_flag("vmx") && !self.has_flag("svm") {
+            VirtSupport::Unsupported
+        } else if kvm.exists() {
+            VirtSupport::Supported
+        } else {
+            VirtSupport::Disabled
+        }
+    }
+    /// The cpufreq scaling governor of the first CPU, e.g. `performance` or `schedutil`.
+    ///
+    /// `None` when cpufreq isn't available, as in most VMs.
+    pub fn governor(&self) -> Option<DisplayBytes<Vec<u8>>> {
+        governor_at(Path::new(CPU_SYSFS))
+    }
+    /// The current frequency averaged across online CPUs, in MHz.
+    ///
+    /// `None` when cpufreq isn't available, as in most VMs.
+    pub fn current_mhz(&self) -> Option<u32> {
+        current_mhz_at(Path::new(CPU_SYSFS))
+    }
+    /// See [`caches`].
+    pub fn caches(&self) -> impl Iterator<Item = CacheInfo> {
+        caches()
+    }
+}
+
+fn governor_at(root: &Path) -> Option<DisplayBytes<Vec<u8>>> {
+    Some(trim_text(
+        fs::read(root.join("cpu0/cpufreq/scaling_governor")).ok()?,
+    ))
+}
+
+/// The `cpuN` directories under `root`, skipping `cpufreq`, `cpuidle` and the like.
+fn cpu_dirs(root: &Path) -> Option<impl Iterator<Item = PathBuf>> {
+    Some(fs::read_dir(root).ok()?.flatten().filter_map(|entry| {
+        let name = entry.file_name();
+        let index = name.as_bytes().strip_prefix(b"cpu")?;
+        (!index.is_empty() && index.iter().all(u8::is_ascii_digit)).then(|| entry.path())
+    }))
+}
+
+fn current_mhz_at(root: &Path) -> Option<u32> {
+    let (mut total_khz, mut cpus) = (0u64, 0u64);
+    for path in cpu_dirs(root)? {
+        // cpu0 usually can't be taken offline and has no `online` attribute
+        if fs::read(path.join("online")).is_ok_and(|online| online.trim_ascii() == b"0") {
+            continue;
+        }
+        let Ok(freq) = fs::read(path.join("cpufreq/scaling_cur_freq")) else {
+            continue;
+        };
+        if let Some(khz) = sysfs_decimal::<u64>(&freq) {
+            total_khz += khz;
+            cpus += 1;
+        }
+    }
+    (cpus != 0).then(|| (total_khz / cpus / 1000) as u32)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CacheKind {
+    Data,
+    Instruction,
+    Unified,
+}
+
+impl CacheKind {
+    fn parse(kind: &[u8]) -> Option<Self> {
+        match kind.trim_ascii_end() {
+            b"Data" => Some(CacheKind::Data),
+            b"Instruction" => Some(CacheKind::Instruction),
+            b"Unified" => Some(CacheKind::Unified),
+            _ => None,
+        }
+    }
+}
+
+/// One level of the cache hierarchy, created by [`CpuInfo::caches`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheInfo {
+    pub level: u8,
+    pub kind: CacheKind,
+    /// The size of one cache, in bytes.
+    pub size: u64,
+    /// How many separate caches of this level and kind there are, e.g. one L1 per core.
+    pub instances: u32,
+}
+
+impl CacheInfo {
+    /// The combined size of every instance, in bytes.
+    pub fn total(&self) -> u64 {
+        self.size * u64::from(self.instances)
+    }
+}
+
+/// `L1d`, `L1i` or `L2` as `lscpu` names them.
+impl fmt::Display for CacheInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "L{}", self.level)?;
+        match self.kind {
+            CacheKind::Data => f.write_str("d"),
+            CacheKind::Instruction => f.write_str("i"),
+            CacheKind::Unified => Ok(()),
+        }
+    }
+}
+
+/// Parses a cache `size` attribute such as `48K` or `32M`.
+fn parse_cache_size(size: &[u8]) -> Option<u64> {
+    let size = size.trim_ascii_end();
+    let (digits, unit) = match size.split_last()? {
+        (b'K', digits) => (digits, 1 << 10),
+        (b'M', digits) => (digits, 1 << 20),
+        (b'G', digits) => (digits, 1 << 30),
+        _ => (size, 1),
+    };
+    sysfs_decimal::<u64>(digits)?.checked_mul(unit)
+}
+
+/// The cache levels from `/sys/devices/system/cpu/cpu*/cache`, ordered by level, kind and size.
+///
+/// Each entry counts the separate caches across all CPUs, so a shared L3 is counted once
+/// while a per-core L1 is counted once per core. Hybrid CPUs whose core types have differently
+/// sized caches get an entry per size. Empty when the kernel doesn't expose caches.
+pub fn caches() -> impl Iterator<Item = CacheInfo> {
+    caches_at(Path::new(CPU_SYSFS)).into_iter()
+}
+
+fn caches_at(root: &Path) -> Vec<CacheInfo> {
+    // Caches shared between CPUs show up under each of them, so they're told apart by the
+    // CPUs sharing them rather than counted once per CPU
+    let mut seen: Vec<(u8, CacheKind, u64, Vec<u8>)> = Vec::new();
+    for cpu in cpu_dirs(root).into_iter().flatten() {
+        let Ok(indices) = fs::read_dir(cpu.join("cache")) else {
+            continue;
+        };
+        for index in indices.flatten() {
+            if !index.file_name().as_bytes().starts_with(b"index") {
+                continue;
+            }
+            let index = index.path();
+            let read = |attr: &str| fs::read(index.join(attr)).ok();
+            let (Some(level), Some(kind), Some(size)) = (
+                read("level").and_then(|level| sysfs_decimal::<u8>(&level)),
+                read("type").and_then(|kind| CacheKind::parse(&kind)),
+                read("size").and_then(|size| parse_cache_size(&size)),
+            ) else {
+                continue;
+            };
+            let shared = read("shared_cpu_list").unwrap_or_default();
+            let cache = (level, kind, size, shared);
+            if !seen.contains(&cache) {
+                seen.push(cache);
+            }
+        }
+    }
+    seen.sort_unstable();
+    let mut caches: Vec<CacheInfo> = Vec::new();
+    for (level, kind, size, _) in seen {
+        match caches.last_mut() {
+            Some(last) if (last.level, last.kind, last.size) == (level, kind, size) => {
+                last.instances += 1
+            }
+            _ => caches.push(CacheInfo {
+                level,
+                kind,
+                size,
+                instances: 1,
+            }),
+        }
+    }
+    caches
+}
+
+/// Time spent by a CPU since boot, in jiffies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct CpuTimes {
+    idle: u64,
+    total: u64,
+}
+
+impl CpuTimes {
+    /// Parses the columns of a `cpu` row: user, nice, system, idle, iowait, irq, softirq and steal.
+    /// The guest columns are already included in user and nice.
+    fn parse(columns: &[u8]) -> Option<Self> {
+        let mut times = [0u64; 8];
+        let mut columns = columns
+            .split(u8::is_ascii_whitespace)
+            .filter(|column| !column.is_empty());
+        for time in &mut times {
+            *time = sysfs_decimal(columns.next()?)?;
+        }
+        // The sums wrap like the counters do, see `usage_until`
+        Some(Self {
+            idle: times[3].wrapping_add(times[4]),
+            total: times
+                .iter()
+                .fold(0, |total, &time| total.wrapping_add(time)),
+        })
+    }
+    /// The busy percentage between `self` and a later sample.
+    fn usage_until(self, later: Self) -> f32 {
+        // Wrapping keeps the difference right if a counter overflowed in between
+        let busy = later
+            .total
+            .wrapping_sub(later.idle)
+            .wrapping_sub(self.total.wrapping_sub(self.idle));
+        // iowait can go backwards, which would otherwise count as busy time
+        let idle = later.idle.wrapping_sub(self.idle);
+        let idle = if (idle as i64) < 0 { 0 } else { idle };
+        let total = busy.saturating_add(idle);
+        if total == 0 {
+            return 0.0;
+        }
+        busy as f32 / total as f32 * 100.0
+    }
+}
+
+/// The `cpu` row and the `cpuN` rows of `/proc/stat`, by core number.
+fn parse_stat(buf: &[u8]) -> (Option<CpuTimes>, Vec<(u32, CpuTimes)>) {
+    let mut overall = None;
+    let mut cores = Vec::new();
+    for (key, columns) in key_values(buf, b' ') {
+        let Some(core) = key.strip_prefix(b"cpu") else {
+            continue;
+        };
+        let Some(times) = CpuTimes::parse(columns) else {
+            continue;
+        };
+        match core {
+            b"" => overall = Some(times),
+            core => {
+                if let Some(core) = sysfs_decimal(core) {
+                    cores.push((core, times));
+                }
+            }
+        }
+    }
+    (overall, cores)
+}
+
+/// CPU utilization over a sampling interval, in percent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuUsage {
+    pub overall: f32,
+    /// Cores by number, only including those that were online for both samples.
+    pub cores: Vec<(u32, f32)>,
+}
+
+impl CpuUsage {
+    fn between(before: &[u8], after: &[u8]) -> Option<Self> {
+        let (before_overall, before_cores) = parse_stat(before);
+        let (after_overall, after_cores) = parse_stat(after);
+        let cores = after_cores
+            .into_iter()
+            .filter_map(|(core, after)| {
+                let &(_, before) = before_cores.iter().find(|&&(c, _)| c == core)?;
+                Some((core, before.usage_until(after)))
+            })
+            .collect();
+        Some(Self {
+            overall: before_overall?.usage_until(after_overall?),
+            cores,
+        })
+    }
+}
+
+/// Samples `/proc/stat` twice, `sample` apart, blocking the calling thread in between.
+pub fn usage(sample: Duration) -> io::Result<CpuUsage> {
+    let before = fs::read(PROC_STAT)?;
+    thread::sleep(sample);
+    let after = fs::read(PROC_STAT)?;
+    CpuUsage::between(&before, &after)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed /proc/stat"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn model_and_threads() {
+        let info = CpuInfo::from_bytes(
+            b"processor\t: 0\nvendor_id\t: AuthenticAMD\ncpu family\t: 25\nmodel\t\t: 97\n\
+              model name\t: AMD Ryzen 9 7950X 16-Core Processor\nstepping\t: 2\n\n\
+              processor\t: 1\nvendor_id\t: AuthenticAMD\nmodel name\t: AMD Ryzen 9 7950X 16-Core Processor\n\n"
+                .to_vec(),
+        );
+        assert_eq!(
+            info.model_name().unwrap().to_string(),
+            "AMD Ryzen 9 7950X 16-Core Processor"
+        );
+        assert_eq!(info.threads(), 2);
+        assert_eq!(info.vendor(), CpuVendor::Amd);
+        assert_eq!(
+            info.signature(),
+            Some(CpuSignature {
+                family: 25,
+                model: 97,
+                stepping: 2
+            })
+        );
+        let debug = format!("{info:?}");
+        assert!(debug.contains("vendor: Amd"));
+        assert!(debug.contains("family: 25"));
+
+        let unknown = CpuInfo::from_bytes(b"processor\t: 0\nCPU implementer\t: 0x00\n".to_vec());
+        assert!(format!("{unknown:?}").contains("model_name: None"));
+    }
+
+    #[test]
+    fn stat_usage() {
+        let before = b"cpu  100 0 100 700 100 0 0 0 0 0\n\
+            cpu0 50 0 50 350 50 0 0 0 0 0\n\
+            cpu1 50 0 50 350 50 0 0 0 0 0\n\
+            intr 12345 0 0\n";
+        // cpu1 went offline, cpu0 was fully busy
+        let after = b"cpu  300 0 100 700 100 0 0 0 0 0\n\
+            cpu0 250 0 50 350 50 0 0 0 0 0\n\
+            ctxt 999\n";
+        let usage = CpuUsage::between(before, after).unwrap();
+        assert_eq!(usage.overall, 100.0);
+        assert_eq!(usage.cores, [(0, 100.0)]);
+
+        let wrapped = CpuTimes {
+            idle: u64::MAX - 10,
+            total: u64::MAX - 20,
+        };
+        let later = CpuTimes { idle: 9, total: 79 };
+        assert_eq!(wrapped.usage_until(later), 80.0);
+        // iowait went down by more than idle went up, all of the time in between was busy
+        let usage = CpuUsage::between(
+            b"cpu  100 0 100 700 100 0 0 0\n",
+            b"cpu  200 0 100 710 60 0 0 0\n",
+        )
+        .unwrap();
+        assert_eq!(usage.overall, 100.0);
+        // Counters large enough to overflow the total
+        let huge = format!("{0} 0 0 {0} 10 0 0 0", u64::MAX - 5);
+        let times = CpuTimes::parse(huge.as_bytes()).unwrap();
+        assert_eq!(times.idle, 4);
+        assert_eq!(times.total, u64::MAX - 1);
+        assert!(CpuUsage::between(b"", b"").is_none());
+    }
+
+    #[test]
+    fn caches() {
+        let root = TempDir::new("cache");
+        // A hybrid CPU: two P-cores with their own 2M L2, two E-cores sharing a 4M one
+        for (cpu, l2_size, l2_shared) in [
+            ("cpu0", "2048K", "0"),
+            ("cpu1", "2048K", "1"),
+            ("cpu2", "4096K", "2-3"),
+            ("cpu3", "4096K", "2-3"),
+        ] {
+            for (index, level, kind, size, shared) in [
+                (
+                    "index0",
+                    "1",
+                    "Data",
+                    "48K",
+                    cpu.strip_prefix("cpu").unwrap(),
+                ),
+                (
+                    "index1",
+                    "1",
+                    "Instruction",
+                    "32K",
+                    cpu.strip_prefix("cpu").unwrap(),
+                ),
+                ("index2", "2", "Unified", l2_size, l2_shared),
+                ("index3", "3", "Unified", "32M", "0-3"),
+            ] {
+                let dir = root.join(cpu).join("cache").join(index);
+                fs::create_dir_all(&dir).unwrap();
+                fs::write(dir.join("level"), format!("{level}\n")).unwrap();
+                fs::write(dir.join("type"), format!("{kind}\n")).unwrap();
+                fs::write(dir.join("size"), format!("{size}\n")).unwrap();
+                fs::write(dir.join("shared_cpu_list"), format!("{shared}\n")).unwrap();
+            }
+        }
+        fs::create_dir_all(root.join("cpuidle")).unwrap();
+
+        let caches = caches_at(&root);
+        let summary: Vec<_> = caches
+            .iter()
+            .map(|cache| (cache.to_string(), cache.size, cache.instances))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("L1d".to_string(), 48 << 10, 4),
+                ("L1i".to_string(), 32 << 10, 4),
+                ("L2".to_string(), 2 << 20, 2),
+                ("L2".to_string(), 4 << 20, 1),
+                ("L3".to_string(), 32 << 20, 1),
+            ]
+        );
+        let l2: u64 = caches[2..4].iter().map(CacheInfo::total).sum();
+        assert_eq!(l2, 8 << 20);
+        assert_eq!(caches[4].total(), 32 << 20);
+        assert_eq!(parse_cache_size(b"1G\n"), Some(1 << 30));
+        assert_eq!(parse_cache_size(b"512\n"), Some(512));
+        assert_eq!(parse_cache_size(b"K\n"), None);
+        fs::remove_dir_all(&root).unwrap();
+        assert!(caches_at(&root).is_empty());
+    }
+
+    #[test]
+    fn cpufreq() {
+        let root = TempDir::new("cpufreq");
+        for (cpu, online, khz) in [
+            ("cpu0", None, "4400000\n"),
+            ("cpu1", Some("1\n"), "4600000\n"),
+            ("cpu2", Some("0\n"), "400000\n"),
+        ] {
+            fs::create_dir_all(root.join(cpu).join("cpufreq")).unwrap();
+            fs::write(root.join(cpu).join("cpufreq/scaling_cur_freq"), khz).unwrap();
+            if let Some(online) = online {
+                fs::write(root.join(cpu).join("online"), online).unwrap();
+            }
+        }
+        fs::write(root.join("cpu0/cpufreq/scaling_governor"), "performance\n").unwrap();
+        fs::create_dir_all(root.join("cpufreq")).unwrap();
+
+        assert_eq!(current_mhz_at(&root), Some(4500));
+        assert_eq!(governor_at(&root).unwrap().to_string(), "performance");
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(current_mhz_at(&root), None);
+        assert!(governor_at(&root).is_none());
+    }
+
+    #[test]
+    fn arm_model() {
+        let info = CpuInfo::from_bytes(
+            b"processor\t: 0\nBogoMIPS\t: 108.00\nCPU implementer\t: 0x41\nCPU part\t: 0xd0b\n\n"
+                .to_vec(),
+        );
+        assert_eq!(
+            info.model_name_or(|| Some(b"Raspberry Pi 5 Model B Rev 1.0\0".to_vec()))
+                .unwrap()
+                .to_string(),
+            "Raspberry Pi 5 Model B Rev 1.0"
+        );
+        assert_eq!(
+            info.model_name_or(|| None).unwrap().to_string(),
+            "ARM Cortex-A76"
+        );
+        let unknown = CpuInfo::from_bytes(b"CPU implementer\t: 0x48\nCPU part\t: 0xd01\n".to_vec());
+        assert_eq!(
+            unknown.model_name_or(|| None).unwrap().to_string(),
+            "0x48:0xd01"
+        );
+        assert_eq!(info.vendor(), CpuVendor::Arm);
+        assert_eq!(unknown.vendor(), CpuVendor::Other);
+        assert_eq!(info.signature(), None);
+        assert!(CpuInfo::from_bytes(Vec::new())
+            .model_name_or(|| None)
+            .is_none());
+    }
+
+    #[test]
+    fn flags() {
+        let info = CpuInfo::from_bytes(
+            b"processor\t: 0\nflags\t\t: fpu vme sse4_2 aes avx2  avx512f avx512bw\n\n".to_vec(),
+        );
+        assert_eq!(info.flags().count(), 7);
+        assert!(info.has_aes());
+        assert!(info.has_avx512());
+        assert!(!info.has_flag("sse"));
+
+        let arm = CpuInfo::from_bytes(b"processor\t: 0\nFeatures\t: fp asimd aes\n".to_vec());
+        assert!(arm.has_aes());
+        assert!(!arm.has_avx512());
+    }
+
+    #[test]
+    fn virtualization() {
+        let present = std::env::temp_dir();
+        let missing = Path::new("/nonexistent/kvm");
+        let intel = CpuInfo::from_bytes(b"processor\t: 0\nflags\t\t: fpu vmx sse4_2\n".to_vec());
+        assert_eq!(intel.virtualization_with(&present), VirtSupport::Supported);
+        assert_eq!(intel.virtualization_with(missing), VirtSupport::Disabled);
+        let amd = CpuInfo::from_bytes(b"processor\t: 0\nflags\t\t: fpu svm\n".to_vec());
+        assert_eq!(amd.virtualization_with(&present), VirtSupport::Supported);
+        let neither = CpuInfo::from_bytes(b"processor\t: 0\nflags\t\t: fpu hypervisor\n".to_vec());
+        assert_eq!(
+            neither.virtualization_with(&present),
+            VirtSupport::Unsupported
+        );
+    }
+}
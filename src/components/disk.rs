@@ -0,0 +1,133 @@
+//! Filesystem usage and type of a path.
+use crate::display::DisplayBytes;
+use std::{
+    ffi::{CString, OsStr},
+    fs, io,
+    mem::MaybeUninit,
+    os::unix::ffi::OsStrExt,
+    path::Path,
+};
+
+const PROC_MOUNTS: &str = "/proc/mounts";
+
+/// Space on a filesystem, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+    pub total: u64,
+    /// Space available to unprivileged users, excluding blocks reserved for root.
+    pub available: u64,
+    pub free: u64,
+}
+
+impl DiskUsage {
+    /// The usage of the filesystem containing `path`, from `statvfs`.
+    // The field types are narrower than u64 on some 32-bit targets
+    #[allow(clippy::unnecessary_cast)]
+    pub fn of(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())?;
+        let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: path is NUL terminated and stat is valid for writes
+        if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: statvfs succeeded, so stat is initialized
+        let stat = unsafe { stat.assume_init() };
+        let block = stat.f_frsize as u64;
+        Ok(Self {
+            total: stat.f_blocks as u64 * block,
+            available: stat.f_bavail as u64 * block,
+            free: stat.f_bfree as u64 * block,
+        })
+    }
+    /// Space used by files, not counting reserved blocks.
+    ///
+    /// Zero if the filesystem reports more free space than its size, as some network ones do.
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+}
+
+/// The type of the filesystem containing `path`, e.g. `ext4` or `btrfs`.
+///
+/// `None` if `path` doesn't exist or `/proc/mounts` can't be read.
+pub fn fs_type(path: impl AsRef<Path>) -> Option<DisplayBytes<Vec<u8>>> {
+    let path = fs::canonicalize(path).ok()?;
+    let mounts = fs::read(PROC_MOUNTS).ok()?;
+    mount_type(&mounts, &path).map(|fstype| DisplayBytes(fstype.to_vec()))
+}
+
+/// Finds the mount with the longest mount point containing `path`.
+///
+/// Later entries win ties, since they're mounted over earlier ones, e.g. by bind mounts or overlayfs.
+fn mount_type<'m>(mounts: &'m [u8], path: &Path) -> Option<&'m [u8]> {
+    let mut best: Option<(usize, &[u8])> = None;
+    for line in mounts.split(|&b| b == b'\n') {
+        let mut fields = line.split(|&b| b == b' ');
+        let (Some(_), Some(mount_point), Some(fstype)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let mount_point = unescape(mount_point);
+        if !path.starts_with(OsStr::from_bytes(&mount_point)) {
+            continue;
+        }
+        if best.is_none_or(|(len, _)| mount_point.len() >= len) {
+            best = Some((mount_point.len(), fstype));
+        }
+    }
+    best.map(|(_, fstype)| fstype)
+}
+
+/// Decodes the octal escapes `/proc/mounts` uses for spaces, tabs, newlines and backslashes.
+fn unescape(field: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(field.len());
+    let mut idx = 0;
+    while idx < field.len() {
+        let escape = field.get(idx + 1..idx + 4).filter(|_| field[idx] == b'\\');
+        match escape {
+            Some(&[a, b, c]) if [a, b, c].iter().all(|d| (b'0'..=b'7').contains(d)) => {
+                out.push((a - b'0') << 6 | (b - b'0') << 3 | (c - b'0'));
+                idx += 4;
+            }
+            _ => {
+                out.push(field[idx]);
+                idx += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTS: &[u8] = b"proc /proc proc rw,nosuid 0 0\n\
+        /dev/nvme0n1p2 / ext4 rw,relatime 0 0\n\
+        /dev/nvme0n1p3 /home btrfs rw,subvol=/home 0 0\n\
+        /dev/sdb1 /mnt/My\\040Disk xfs rw 0 0\n\
+        overlay /home overlay rw,lowerdir=/a 0 0\n";
+
+    #[test]
+    fn longest_mount_point() {
+        let fstype = |path: &str| mount_type(MOUNTS, Path::new(path));
+        assert_eq!(fstype("/etc/fstab"), Some(&b"ext4"[..]));
+        assert_eq!(fstype("/home/user"), Some(&b"overlay"[..]));
+        assert_eq!(fstype("/homes"), Some(&b"ext4"[..]));
+        assert_eq!(fstype("/mnt/My Disk/photos"), Some(&b"xfs"[..]));
+        assert_eq!(fstype("/proc"), Some(&b"proc"[..]));
+        assert!(DiskUsage::of("/").unwrap().total > 0);
+    }
+
+    #[test]
+    fn used() {
+        let usage = |total, free| DiskUsage {
+            total,
+            available: free,
+            free,
+        };
+        assert_eq!(usage(100, 40).used(), 60);
+        assert_eq!(usage(100, 120).used(), 0);
+    }
+}
@@ -0,0 +1,83 @@
+//! UEFI firmware state, from the variables exposed in `/sys/firmware/efi/efivars`.
+use std::{fmt, fs, path::Path};
+
+/// Only present when the kernel was booted through UEFI.
+const EFI_DIR: &str = "/sys/firmware/efi";
+
+/// `SecureBoot` in the EFI global variable namespace, `8be4df61-93ca-11d2-aa0d-00e098032b8c`.
+const SECURE_BOOT_VAR: &str =
+    "/sys/firmware/efi/efivars/SecureBoot-8be4df61-93ca-11d2-aa0d-00e098032b8c";
+
+/// The kind of firmware the system was booted through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Firmware {
+    Uefi,
+    /// Legacy BIOS, including UEFI's compatibility support module.
+    Bios,
+}
+
+impl fmt::Display for Firmware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Firmware::Uefi => "UEFI",
+            Firmware::Bios => "BIOS",
+        })
+    }
+}
+
+/// Whether the system booted through UEFI or BIOS, from the existence of `/sys/firmware/efi`.
+///
+/// Only meaningful on Linux, elsewhere this is always [`Firmware::Bios`].
+pub fn firmware() -> Firmware {
+    firmware_at(Path::new(EFI_DIR))
+}
+
+fn firmware_at(efi_dir: &Path) -> Firmware {
+    match efi_dir.is_dir() {
+        true => Firmware::Uefi,
+        false => Firmware::Bios,
+    }
+}
+
+/// Whether Secure Boot is enabled.
+///
+/// `None` on BIOS systems, when efivarfs isn't mounted, or when the variable can't be read.
+pub fn secure_boot() -> Option<bool> {
+    parse_bool_var(&fs::read(SECURE_BOOT_VAR).ok()?)
+}
+
+/// efivarfs prefixes the data with the variable's 4 attribute bytes,
+/// a boolean variable holds a single byte after them.
+fn parse_bool_var(var: &[u8]) -> Option<bool> {
+    match var {
+        [_, _, _, _, .., value] => Some(*value == 1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn boot_mode() {
+        let tmp = TempDir::new("efi");
+        let dir = tmp.join("efi");
+        assert_eq!(firmware_at(&dir), Firmware::Bios);
+        fs::write(&dir, "").unwrap();
+        assert_eq!(firmware_at(&dir), Firmware::Bios);
+        fs::remove_file(&dir).unwrap();
+        fs::create_dir(&dir).unwrap();
+        assert_eq!(firmware_at(&dir), Firmware::Uefi);
+    }
+
+    #[test]
+    fn bool_var() {
+        assert_eq!(parse_bool_var(&[0x06, 0, 0, 0, 1]), Some(true));
+        assert_eq!(parse_bool_var(&[0x06, 0, 0, 0, 0]), Some(false));
+        // Attributes without data
+        assert_eq!(parse_bool_var(&[0x06, 0, 0, 0]), None);
+        assert_eq!(parse_bool_var(&[]), None);
+    }
+}
@@ -0,0 +1,31 @@
+//! The init system, i.e. the process running as PID 1.
+use crate::{display::DisplayBytes, parse::trim_text};
+use std::fs;
+
+const PID1_COMM: &str = "/proc/1/comm";
+
+/// The name of PID 1, e.g. `systemd`, `runit`, `s6-svscan` or `dinit`.
+///
+/// Empty when `/proc/1/comm` can't be read, which happens in some sandboxes.
+pub fn init_system() -> DisplayBytes<Vec<u8>> {
+    from_comm(fs::read(PID1_COMM).unwrap_or_default())
+}
+
+fn from_comm(comm: Vec<u8>) -> DisplayBytes<Vec<u8>> {
+    trim_text(comm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comm() {
+        assert_eq!(from_comm(b"runit\n".to_vec()).to_string(), "runit");
+        assert_eq!(
+            from_comm(b"openrc-init\n".to_vec()).to_string(),
+            "openrc-init"
+        );
+        assert!(from_comm(Vec::new()).is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+//! Memory usage from `/proc/meminfo`.
+use crate::parse::{decimal, key_values};
+use std::{fs, io};
+use winnow::{ascii::space1, combinator::opt, Parser};
+
+const MEMINFO: &str = "/proc/meminfo";
+
+/// Memory statistics, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemInfo {
+    pub total: u64,
+    pub available: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+}
+
+impl MemInfo {
+    pub fn read() -> io::Result<Self> {
+        Self::parse(&fs::read(MEMINFO)?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed /proc/meminfo"))
+    }
+    /// Parses the contents of `/proc/meminfo`, `None` if `MemTotal` or `MemAvailable` are missing.
+    pub fn parse(buf: &[u8]) -> Option<Self> {
+        let mut total = None;
+        let mut available = None;
+        let mut info = Self::default();
+        for (key, value) in key_values(buf, b':') {
+            let field = match key {
+                b"MemTotal" => total.insert(0),
+                b"MemAvailable" => available.insert(0),
+                b"SwapTotal" => &mut info.swap_total,
+                b"SwapFree" => &mut info.swap_free,
+                _ => continue,
+            };
+            *field = parse_kib(value)?;
+        }
+        info.total = total?;
+        info.available = available?;
+        Some(info)
+    }
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+}
+
+/// Parses a value such as `32768000 kB` into bytes, `None` if it overflows.
+fn parse_kib(value: &[u8]) -> Option<u64> {
+    (decimal::<u64>, opt((space1, b"kB")))
+        .parse(value)
+        .ok()?
+        .0
+        .checked_mul(1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meminfo() {
+        let info = MemInfo::parse(
+            b"MemTotal:       32768000 kB\nMemFree:         1024000 kB\n\
+              MemAvailable:   16384000 kB\nSwapTotal:       8388604 kB\nSwapFree:        8388604 kB\n",
+        )
+        .unwrap();
+        assert_eq!(info.total, 32768000 * 1024);
+        assert_eq!(info.used(), 16384000 * 1024);
+        assert_eq!(info.swap_free, 8388604 * 1024);
+        assert_eq!(MemInfo::parse(b"MemTotal: 1 kB\n"), None);
+        let huge = format!("MemTotal: {} kB\nMemAvailable: 1 kB\n", u64::MAX / 1024 + 1);
+        assert_eq!(MemInfo::parse(huge.as_bytes()), None);
+    }
+}
@@ -0,0 +1,794 @@
+//! The pieces of information shown by the fetch.
+//!
+//! Each module exposes typed getters, e.g. [`cpu::CpuInfo`] or [`memory::MemInfo`].
+//! The unit structs here wrap those getters behind the uniform [`Component`] interface,
+//! gathering the data when [`Component::value`] is called and formatting it into a single line.
+pub mod audio;
+pub mod cpu;
+pub mod disk;
+pub mod firmware;
+pub mod init;
+pub mod memory;
+pub mod modules;
+pub mod name;
+pub mod network;
+pub mod process;
+pub mod random;
+pub mod session;
+pub mod sysctl;
+pub mod terminal;
+#[cfg(feature = "timing")]
+pub mod timing;
+pub mod uptime;
+
+use crate::{bytesize::ByteSize, pci::PciBackendError};
+use std::{fmt, io, sync::mpsc, thread};
+
+#[derive(Debug)]
+pub enum ComponentError {
+    /// The information isn't available on this system.
+    Unavailable,
+    /// Neither sysfs nor procfs is mounted, so PCI devices can't be enumerated,
+    /// e.g. in a container. Reported once as `PCI: unavailable` by the fetch.
+    PciUnavailable,
+    IOError(io::Error),
+    Pci(PciBackendError),
+}
+
+impl From<io::Error> for ComponentError {
+    fn from(err: io::Error) -> Self {
+        ComponentError::IOError(err)
+    }
+}
+
+impl From<PciBackendError> for ComponentError {
+    fn from(err: PciBackendError) -> Self {
+        ComponentError::Pci(err)
+    }
+}
+
+impl fmt::Display for ComponentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentError::Unavailable => f.write_str("not available"),
+            ComponentError::PciUnavailable => f.write_str("PCI not available"),
+            ComponentError::IOError(err) => write!(f, "IO error: {err}"),
+            ComponentError::Pci(err) => write!(f, "PCI error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ComponentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ComponentError::Unavailable | ComponentError::PciUnavailable => None,
+            ComponentError::IOError(err) => Some(err),
+            ComponentError::Pci(err) => Some(err),
+        }
+    }
+}
+
+/// A single `label: value` line of the fetch.
+///
+/// Implement it to add lines rxfetch doesn't ship, and pass them to
+/// [`Fetch::with`](crate::fetch::Fetch::with) or [`Fetch::custom`](crate::fetch::Fetch::custom).
+///
+/// Components are gathered concurrently by [`gather`], so they must be shareable between threads.
+/// Each is gathered once per fetch, on a thread of its own, so [`Component::value`] may block.
+pub trait Component: Send + Sync {
+    /// The label shown before the value. Should be cheap, it's read again for every line printed.
+    fn label(&self) -> &str;
+    /// Gathers and formats the value, a single line without the label.
+    ///
+    /// Return [`ComponentError::Unavailable`] when the information doesn't exist on this system,
+    /// such lines are left out of the fetch rather than reported.
+    fn value(&self) -> Result<String, ComponentError>;
+}
+
+/// `user@hostname`.
+pub struct User;
+
+impl Component for User {
+    fn label(&self) -> &str {
+        "User"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let user =
+            name::PwuId::get_alloc(name::current_uid()).map_err(|_| ComponentError::Unavailable)?;
+        Ok(format!(
+            "{}@{}",
+            user.name(),
+            name::SystemName::get().nodename()
+        ))
+    }
+}
+
+/// The kernel release.
+pub struct Kernel;
+
+impl Component for Kernel {
+    fn label(&self) -> &str {
+        "Kernel"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let name = name::SystemName::get();
+        let release = name.release_opt().ok_or(ComponentError::Unavailable)?;
+        Ok(release.to_string())
+    }
+}
+
+/// Time since boot, e.g. `3 days, 4 hours, 12 minutes`.
+pub struct Uptime;
+
+impl Component for Uptime {
+    fn label(&self) -> &str {
+        "Uptime"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        Ok(uptime::Uptime::get()?
+            .with_style(uptime::UptimeStyle::Verbose)
+            .to_string())
+    }
+}
+
+/// When the system booted, as a local date and time.
+///
+/// Not shown by default.
+pub struct BootTime;
+
+impl Component for BootTime {
+    fn label(&self) -> &str {
+        "Booted"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let booted = uptime::boot_time().ok_or(ComponentError::Unavailable)?;
+        let local = uptime::LocalTime::from_system(booted).ok_or(ComponentError::Unavailable)?;
+        Ok(local.to_string())
+    }
+}
+
+/// The init system.
+pub struct Init;
+
+impl Component for Init {
+    fn label(&self) -> &str {
+        "Init"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let init = init::init_system();
+        if init.is_empty() {
+            return Err(ComponentError::Unavailable);
+        }
+        Ok(init.to_string())
+    }
+}
+
+/// Wayland, X11 or TTY, see [`session::session_protocol`].
+pub struct Session;
+
+impl Component for Session {
+    fn label(&self) -> &str {
+        "Session"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        Ok(session::session_protocol().to_string())
+    }
+}
+
+/// The login manager the session was started from, e.g. `sddm`.
+///
+/// Not shown by default.
+pub struct DisplayManager;
+
+impl Component for DisplayManager {
+    fn label(&self) -> &str {
+        "Display Manager"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let dm = process::display_manager().ok_or(ComponentError::Unavailable)?;
+        Ok(dm.to_string())
+    }
+}
+
+/// The CPU model and thread count.
+pub struct Cpu;
+
+impl Component for Cpu {
+    fn label(&self) -> &str {
+        "CPU"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let info = cpu::CpuInfo::read()?;
+        let model = info.model_name().ok_or(ComponentError::Unavailable)?;
+        let mut value = format!("{model} ({})", info.threads());
+        if let Some(mhz) = info.current_mhz() {
+            value += &format!(" @ {:.1}GHz", mhz as f32 / 1000.0);
+        }
+        Ok(value)
+    }
+}
+
+/// Only a failure to enumerate devices at all means PCI is unavailable, a device missing a field
+/// reports [`PciBackendError::NotAvailable`] as well.
+fn enumeration_error(err: PciBackendError) -> ComponentError {
+    match err {
+        PciBackendError::NotAvailable => ComponentError::PciUnavailable,
+        err => ComponentError::Pci(err),
+    }
+}
+
+/// The GPU with the most VRAM, rather than the boot display, which on laptops is the iGPU.
+fn main_gpu() -> Result<crate::pci::PciDevice<crate::pci::AutoProvider>, ComponentError> {
+    use crate::pci::{gpu::by_vram, PciAutoIter, PciClass, PciDevIterBackend};
+    let gpus = PciAutoIter::try_init()
+        .map_err(enumeration_error)?
+        .filter_class(PciClass::DISPLAY)
+        .flatten();
+    by_vram(gpus)
+        .into_iter()
+        .next()
+        .ok_or(ComponentError::Unavailable)
+}
+
+/// The GPU with the most VRAM, picked like [`crate::pci::PciAutoIter::gpus_by_vram`] does.
+///
+/// Shows its temperature, utilization and power draw when the driver exposes them.
+pub struct Gpu;
+
+impl Component for Gpu {
+    fn label(&self) -> &str {
+        "GPU"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let mut gpu = main_gpu()?;
+        let stats = [
+            gpu.temperature().map(|temp| format!("{temp:.0}°C")),
+            gpu.utilization().map(|busy| format!("{busy}%")),
+            gpu.power_draw()
+                .ok()
+                .flatten()
+                .map(|watts| format!("{watts:.0} W")),
+        ];
+        let label = gpu.label().ok().flatten();
+        let device = gpu.resolved()?;
+        let mut value = crate::pci::PrettyName {
+            device: &device,
+            label: label.as_ref().map(|label| label.as_bytes()),
+            db: crate::pci::ids::PciIdDb::system(),
+        }
+        .to_string();
+        let stats: Vec<_> = stats.into_iter().flatten().collect();
+        if !stats.is_empty() {
+            value += &format!(" ({})", stats.join(", "));
+        }
+        Ok(value)
+    }
+}
+
+/// The current core and memory clocks of the same GPU as [`Gpu`], e.g. `2500MHz / 1250MHz`.
+///
+/// Only `amdgpu` exposes them. Not shown by default.
+pub struct GpuClock;
+
+impl Component for GpuClock {
+    fn label(&self) -> &str {
+        "GPU Clock"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let mut gpu = main_gpu()?;
+        let core = gpu.core_clock_mhz().ok_or(ComponentError::Unavailable)?;
+        Ok(match gpu.mem_clock_mhz() {
+            Some(mem) => format!("{core}MHz / {mem}MHz"),
+            None => format!("{core}MHz"),
+        })
+    }
+}
+
+/// Used and total memory.
+pub struct Memory;
+
+impl Component for Memory {
+    fn label(&self) -> &str {
+        "Memory"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let info = memory::MemInfo::read()?;
+        Ok(format!(
+            "{} / {}",
+            ByteSize(info.used()),
+            ByteSize(info.total)
+        ))
+    }
+}
+
+/// Usage and type of the root filesystem.
+pub struct Disk;
+
+impl Component for Disk {
+    fn label(&self) -> &str {
+        "Disk (/)"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let usage = disk::DiskUsage::of("/")?;
+        let mut value = format!("{} / {}", ByteSize(usage.used()), ByteSize(usage.total));
+        if let Some(fstype) = disk::fs_type("/") {
+            value += &format!(" ({fstype})");
+        }
+        Ok(value)
+    }
+}
+
+/// The number of loaded kernel modules.
+pub struct Modules;
+
+impl Component for Modules {
+    fn label(&self) -> &str {
+        "Modules"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        match modules::count() {
+            0 => Err(ComponentError::Unavailable),
+            count => Ok(count.to_string()),
+        }
+    }
+}
+
+/// Available entropy out of the pool size, and the hardware RNG if there is one.
+///
+/// Not shown by default.
+pub struct Entropy;
+
+impl Component for Entropy {
+    fn label(&self) -> &str {
+        "Entropy"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let available = random::entropy().ok_or(ComponentError::Unavailable)?;
+        let mut value = match random::pool_size() {
+            Some(size) => format!("{available}/{size} bits"),
+            None => format!("{available} bits"),
+        };
+        if let Some(rng) = random::rng_current() {
+            value += &format!(" ({rng})");
+        }
+        Ok(value)
+    }
+}
+
+/// Hardware virtualization support, e.g. `VT-x (supported)`.
+///
+/// Not shown by default.
+pub struct Virtualization;
+
+impl Component for Virtualization {
+    fn label(&self) -> &str {
+        "Virtualization"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let info = cpu::CpuInfo::read()?;
+        let support = info.virtualization();
+        Ok(if info.has_flag("vmx") {
+            format!("VT-x ({support})")
+        } else if info.has_flag("svm") {
+            format!("AMD-V ({support})")
+        } else {
+            support.to_string()
+        })
+    }
+}
+
+/// The total size of each cache level, e.g. `L1d 384.0K, L1i 256.0K, L2 8.0M, L3 32.0M`.
+///
+/// Not shown by default.
+pub struct Cache;
+
+impl Component for Cache {
+    fn label(&self) -> &str {
+        "Cache"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        // Hybrid CPUs list a level once per cache size, shown here as a single total
+        let mut totals: Vec<(cpu::CacheInfo, u64)> = Vec::new();
+        for cache in cpu::caches() {
+            match totals.last_mut() {
+                Some((last, total)) if (last.level, last.kind) == (cache.level, cache.kind) => {
+                    *total += cache.total()
+                }
+                _ => totals.push((cache, cache.total())),
+            }
+        }
+        if totals.is_empty() {
+            return Err(ComponentError::Unavailable);
+        }
+        let caches: Vec<String> = totals
+            .iter()
+            .map(|(cache, total)| format!("{cache} {}", ByteSize(*total)))
+            .collect();
+        Ok(caches.join(", "))
+    }
+}
+
+/// A few memory tunables that power users adjust, e.g. `swappiness 60, max_map_count 65530`.
+///
+/// Not shown by default.
+pub struct Tunables;
+
+impl Component for Tunables {
+    fn label(&self) -> &str {
+        "Tunables"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let tunables: Vec<String> = ["vm.swappiness", "vm.max_map_count"]
+            .into_iter()
+            .filter_map(|key| {
+                let name = key.rsplit('.').next()?;
+                Some(format!("{name} {}", sysctl::read_sysctl(key)?))
+            })
+            .collect();
+        if tunables.is_empty() {
+            return Err(ComponentError::Unavailable);
+        }
+        Ok(tunables.join(", "))
+    }
+}
+
+/// The font of the terminal, for the terminals whose config file can be read.
+///
+/// Not shown by default.
+pub struct TerminalFont;
+
+impl Component for TerminalFont {
+    fn label(&self) -> &str {
+        "Terminal Font"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let font = terminal::terminal_font().ok_or(ComponentError::Unavailable)?;
+        Ok(font.to_string())
+    }
+}
+
+/// The number of processes.
+///
+/// Not shown by default.
+pub struct Processes;
+
+impl Component for Processes {
+    fn label(&self) -> &str {
+        "Processes"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        match process::process_count(false) {
+            (0, _) => Err(ComponentError::Unavailable),
+            (total, _) => Ok(total.to_string()),
+        }
+    }
+}
+
+/// The default ALSA sound card.
+///
+/// Not shown by default.
+pub struct Sound;
+
+impl Component for Sound {
+    fn label(&self) -> &str {
+        "Sound"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let card = audio::default_card().ok_or(ComponentError::Unavailable)?;
+        Ok(card.to_string())
+    }
+}
+
+/// Whether the system booted through UEFI or BIOS.
+///
+/// Not shown by default.
+pub struct Firmware;
+
+impl Component for Firmware {
+    fn label(&self) -> &str {
+        "Firmware"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        // Without sysfs there's nothing to tell the two apart
+        if !cfg!(target_os = "linux") {
+            return Err(ComponentError::Unavailable);
+        }
+        Ok(firmware::firmware().to_string())
+    }
+}
+
+/// Whether UEFI Secure Boot is enabled.
+///
+/// Not shown by default.
+pub struct SecureBoot;
+
+impl Component for SecureBoot {
+    fn label(&self) -> &str {
+        "Secure Boot"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        match firmware::secure_boot().ok_or(ComponentError::Unavailable)? {
+            true => Ok("enabled".to_string()),
+            false => Ok("disabled".to_string()),
+        }
+    }
+}
+
+/// The local IP and the interface it belongs to.
+pub struct LocalIp;
+
+impl Component for LocalIp {
+    fn label(&self) -> &str {
+        "Local IP"
+    }
+    fn value(&self) -> Result<String, ComponentError> {
+        let (name, addr) = network::primary_interface().ok_or(ComponentError::Unavailable)?;
+        Ok(format!("{addr} ({name})"))
+    }
+}
+
+/// The order in which [`gather`] hands out values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputOrder {
+    /// In the order of the components, holding back values that are ready early.
+    #[default]
+    Stable,
+    /// As soon as each value is ready, so the fastest components come first.
+    AsReady,
+}
+
+/// Gathers every component's value on its own thread, calling `f` with each as it becomes available.
+pub fn gather<'c>(
+    components: &'c [Box<dyn Component>],
+    order: OutputOrder,
+    f: impl FnMut(&'c dyn Component, Result<String, ComponentError>),
+) {
+    gather_with(components, order, |component| component.value(), f)
+}
+
+/// Like [`gather`], producing each component's result with `get` on its thread.
+fn gather_with<'c, T: Send>(
+    components: &'c [Box<dyn Component>],
+    order: OutputOrder,
+    get: impl Fn(&dyn Component) -> T + Sync,
+    mut f: impl FnMut(&'c dyn Component, T),
+) {
+    let get = &get;
+    thread::scope(|scope| {
+        let (tx, rx) = mpsc::channel();
+        for (idx, component) in components.iter().enumerate() {
+            let tx = tx.clone();
+            scope.spawn(move || {
+                let _ = tx.send((idx, get(&**component)));
+            });
+        }
+        drop(tx);
+
+        // Values that arrived before an earlier component finished
+        let mut pending: Vec<Option<T>> = components.iter().map(|_| None).collect();
+        let mut next = 0;
+        for (idx, value) in rx {
+            if order == OutputOrder::AsReady {
+                f(&*components[idx], value);
+                continue;
+            }
+            pending[idx] = Some(value);
+            while let Some(value) = pending.get_mut(next).and_then(Option::take) {
+                f(&*components[next], value);
+                next += 1;
+            }
+        }
+    })
+}
+
+/// A component known to the binary, see [`REGISTRY`].
+#[derive(Clone, Copy)]
+pub struct RegistryEntry {
+    /// The name used on the command line, e.g. `local-ip`.
+    pub name: &'static str,
+    /// Whether the component is part of [`default_components`].
+    pub default: bool,
+    pub new: fn() -> Box<dyn Component>,
+}
+
+impl fmt::Debug for RegistryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegistryEntry")
+            .field("name", &self.name)
+            .field("default", &self.default)
+            .finish_non_exhaustive()
+    }
+}
+
+macro_rules! entry {
+    ($name:literal, $component:expr, default) => {
+        RegistryEntry {
+            name: $name,
+            default: true,
+            new: || Box::new($component),
+        }
+    };
+    ($name:literal, $component:expr) => {
+        RegistryEntry {
+            name: $name,
+            default: false,
+            new: || Box::new($component),
+        }
+    };
+}
+
+/// Every component, in the order they're shown.
+///
+/// Adding a component to the fetch only takes an entry here.
+pub static REGISTRY: &[RegistryEntry] = &[
+    entry!("user", User, default),
+    entry!("kernel", Kernel, default),
+    entry!("uptime", Uptime, default),
+    entry!("boot-time", BootTime),
+    entry!("init", Init, default),
+    entry!("session", Session, default),
+    entry!("display-manager", DisplayManager),
+    entry!("cpu", Cpu, default),
+    entry!("cache", Cache),
+    entry!("virtualization", Virtualization),
+    entry!("gpu", Gpu, default),
+    entry!("gpu-clock", GpuClock),
+    entry!("memory", Memory, default),
+    entry!("disk", Disk, default),
+    entry!("modules", Modules, default),
+    entry!("entropy", Entropy),
+    entry!("terminal-font", TerminalFont),
+    entry!("processes", Processes),
+    entry!("sound", Sound),
+    entry!("firmware", Firmware),
+    entry!("secure-boot", SecureBoot),
+    entry!("tunables", Tunables),
+    entry!("local-ip", LocalIp, default),
+];
+
+/// Creates the component registered as `name`, matching case-insensitively.
+pub fn by_name(name: &str) -> Option<Box<dyn Component>> {
+    REGISTRY
+        .iter()
+        .find(|entry| entry.name.eq_ignore_ascii_case(name))
+        .map(|entry| (entry.new)())
+}
+
+/// The components shown by default, in order.
+pub fn default_components() -> Vec<Box<dyn Component>> {
+    REGISTRY
+        .iter()
+        .filter(|entry| entry.default)
+        .map(|entry| (entry.new)())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Condvar, Mutex};
+
+    /// The labels that have been signalled, for components to wait on.
+    #[derive(Default)]
+    struct Signals {
+        done: Mutex<Vec<String>>,
+        changed: Condvar,
+    }
+
+    impl Signals {
+        fn signal(&self, label: &str) {
+            self.done.lock().unwrap().push(label.to_string());
+            self.changed.notify_all();
+        }
+        fn wait_for(&self, label: &str) {
+            let done = self.done.lock().unwrap();
+            let _done = self
+                .changed
+                .wait_while(done, |done| !done.iter().any(|done| done == label))
+                .unwrap();
+        }
+    }
+
+    /// Finishes only once `after` has been signalled, so the order components finish in
+    /// doesn't depend on how long they take.
+    struct Ordered {
+        label: &'static str,
+        after: Option<&'static str>,
+        signals: Arc<Signals>,
+        /// Signals itself when done, rather than leaving it to whoever receives the value.
+        signal_done: bool,
+    }
+
+    impl Component for Ordered {
+        fn label(&self) -> &str {
+            self.label
+        }
+        fn value(&self) -> Result<String, ComponentError> {
+            if let Some(after) = self.after {
+                self.signals.wait_for(after);
+            }
+            if self.signal_done {
+                self.signals.signal(self.label);
+            }
+            Ok(self.label.to_lowercase())
+        }
+    }
+
+    /// Gathers components that finish in the order Fast, Medium, Slow.
+    fn labels(order: OutputOrder) -> Vec<String> {
+        let signals = Arc::new(Signals::default());
+        // Values are only passed on as they arrive with AsReady, so wait for that there,
+        // while with Stable the fast ones are held back until Slow is done
+        let signal_done = order == OutputOrder::Stable;
+        let component = |label, after| -> Box<dyn Component> {
+            Box::new(Ordered {
+                label,
+                after,
+                signals: Arc::clone(&signals),
+                signal_done,
+            })
+        };
+        let components = vec![
+            component("Slow", Some("Medium")),
+            component("Fast", None),
+            component("Medium", Some("Fast")),
+        ];
+        let mut labels = Vec::new();
+        gather(&components, order, |component, value| {
+            assert_eq!(value.unwrap(), component.label().to_lowercase());
+            labels.push(component.label().to_string());
+            if !signal_done {
+                signals.signal(component.label());
+            }
+        });
+        labels
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn pci_unavailable() {
+        let missing = crate::test_util::TempDir::new("no-pci");
+        let err = crate::pci::PciAutoIter::try_init_in(missing.join("sys"), missing.join("proc"))
+            .err()
+            .unwrap();
+        assert!(matches!(err, PciBackendError::NotAvailable));
+        assert!(matches!(
+            enumeration_error(err),
+            ComponentError::PciUnavailable
+        ));
+        assert!(matches!(
+            enumeration_error(PciBackendError::InvalidDevice),
+            ComponentError::Pci(PciBackendError::InvalidDevice)
+        ));
+        // A missing field of a device that does exist isn't
+        assert!(matches!(
+            ComponentError::from(PciBackendError::NotAvailable),
+            ComponentError::Pci(PciBackendError::NotAvailable)
+        ));
+    }
+
+    #[test]
+    fn registry() {
+        let labels: Vec<_> = default_components()
+            .iter()
+            .map(|component| component.label().to_string())
+            .collect();
+        assert_eq!(labels[..2], ["User", "Kernel"]);
+        assert!(!labels.iter().any(|label| label == "Entropy"));
+        assert_eq!(by_name("Local-IP").unwrap().label(), "Local IP");
+        assert!(by_name("nonexistent").is_none());
+        for (idx, entry) in REGISTRY.iter().enumerate() {
+            assert!(
+                !REGISTRY[..idx].iter().any(|other| other.name == entry.name),
+                "{} is registered twice",
+                entry.name
+            );
+        }
+    }
+
+    #[test]
+    fn gather_order() {
+        assert_eq!(labels(OutputOrder::Stable), ["Slow", "Fast", "Medium"]);
+        assert_eq!(labels(OutputOrder::AsReady), ["Fast", "Medium", "Slow"]);
+    }
+}
@@ -0,0 +1,52 @@
+//! Loaded kernel modules, from `/proc/modules`.
+use std::fs;
+
+const PROC_MODULES: &str = "/proc/modules";
+
+/// The number of loaded modules, 0 if `/proc/modules` can't be read.
+pub fn count() -> usize {
+    fs::read(PROC_MODULES).map_or(0, |modules| names(&modules).count())
+}
+
+/// Whether the module `name` is loaded, e.g. `amdgpu` or `nvidia`.
+///
+/// Dashes and underscores are interchangeable, as they are for `modprobe`.
+pub fn module_loaded(name: &str) -> bool {
+    fs::read(PROC_MODULES).is_ok_and(|modules| contains(&modules, name))
+}
+
+/// The first field of every line.
+fn names(modules: &[u8]) -> impl Iterator<Item = &[u8]> {
+    modules
+        .split(|&b| b == b'\n')
+        .filter_map(|line| line.split(|&b| b == b' ').next())
+        .filter(|name| !name.is_empty())
+}
+
+fn contains(modules: &[u8], name: &str) -> bool {
+    let normalize = |b: u8| if b == b'-' { b'_' } else { b };
+    names(modules).any(|module| {
+        module.len() == name.len()
+            && module
+                .iter()
+                .zip(name.bytes())
+                .all(|(&a, b)| normalize(a) == normalize(b))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proc_modules() {
+        let modules = b"amdgpu 14434304 42 - Live 0x0000000000000000\n\
+            snd_hda_intel 61440 3 - Live 0x0000000000000000\n\
+            drm_ttm_helper 12288 1 amdgpu, Live 0x0000000000000000\n";
+        assert_eq!(names(modules).count(), 3);
+        assert!(contains(modules, "amdgpu"));
+        assert!(contains(modules, "snd-hda-intel"));
+        assert!(!contains(modules, "nvidia"));
+        assert!(!contains(modules, "amd"));
+    }
+}
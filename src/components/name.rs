@@ -0,0 +1,637 @@
+//! User, account and system identity lookup.
+use crate::{arrayvec::ArrayVec, display::DisplayBytes, parse::unhex};
+use std::{
+    ffi::{c_char, CStr, OsStr},
+    fmt, fs, io,
+    mem::MaybeUninit,
+    ops::Range,
+    os::unix::ffi::OsStrExt,
+    path::Path,
+};
+
+/// The real user id of the current process.
+pub fn current_uid() -> u32 {
+    // SAFETY: getuid is always successful
+    unsafe { libc::getuid() }
+}
+
+/// The system identification returned by `uname`.
+///
+/// Fields are empty if `uname` failed.
+#[derive(Clone, Copy)]
+pub struct SystemName(libc::utsname);
+
+impl SystemName {
+    pub fn get() -> Self {
+        // SAFETY: utsname is plain data, for which all zeroes is valid
+        let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+        // SAFETY: name is a valid utsname to write into
+        if unsafe { libc::uname(&mut name) } != 0 {
+            // SAFETY: see above
+            name = unsafe { std::mem::zeroed() };
+        }
+        Self(name)
+    }
+    fn field(field: &[c_char]) -> DisplayBytes<&[u8]> {
+        // SAFETY: c_char and u8 have the same layout
+        let bytes: &[u8] = unsafe { &*(field as *const [c_char] as *const [u8]) };
+        let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        DisplayBytes(&bytes[..len])
+    }
+    /// The operating system name, e.g. `Linux`.
+    pub fn sysname(&self) -> DisplayBytes<&[u8]> {
+        Self::field(&self.0.sysname)
+    }
+    /// The hostname.
+    pub fn nodename(&self) -> DisplayBytes<&[u8]> {
+        Self::field(&self.0.nodename)
+    }
+    /// The kernel release, e.g. `6.9.3-arch1-1`.
+    pub fn release(&self) -> DisplayBytes<&[u8]> {
+        Self::field(&self.0.release)
+    }
+    pub fn version(&self) -> DisplayBytes<&[u8]> {
+        Self::field(&self.0.version)
+    }
+    /// The hardware architecture, e.g. `x86_64`.
+    pub fn machine(&self) -> DisplayBytes<&[u8]> {
+        Self::field(&self.0.machine)
+    }
+    /// The NIS domain name.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn domain(&self) -> DisplayBytes<&[u8]> {
+        Self::field(&self.0.domainname)
+    }
+    fn non_empty(field: DisplayBytes<&[u8]>) -> Option<DisplayBytes<&[u8]>> {
+        (!field.is_empty()).then_some(field)
+    }
+    /// Like [`Self::sysname`], but `None` if the field is empty.
+    pub fn sysname_opt(&self) -> Option<DisplayBytes<&[u8]>> {
+        Self::non_empty(self.sysname())
+    }
+    /// Like [`Self::nodename`], but `None` if the field is empty.
+    pub fn nodename_opt(&self) -> Option<DisplayBytes<&[u8]>> {
+        Self::non_empty(self.nodename())
+    }
+    /// Like [`Self::release`], but `None` if the field is empty.
+    pub fn release_opt(&self) -> Option<DisplayBytes<&[u8]>> {
+        Self::non_empty(self.release())
+    }
+    /// Like [`Self::version`], but `None` if the field is empty.
+    pub fn version_opt(&self) -> Option<DisplayBytes<&[u8]>> {
+        Self::non_empty(self.version())
+    }
+    /// Like [`Self::machine`], but `None` if the field is empty.
+    pub fn machine_opt(&self) -> Option<DisplayBytes<&[u8]>> {
+        Self::non_empty(self.machine())
+    }
+    /// Like [`Self::domain`], but `None` if the field is empty or `(none)`, which Linux reports when unset.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn domain_opt(&self) -> Option<DisplayBytes<&[u8]>> {
+        Self::non_empty(self.domain()).filter(|domain| domain.as_bytes() != b"(none)")
+    }
+    /// Copies the fields out, so they can be kept without the whole `utsname`.
+    pub fn into_owned(self) -> OwnedSystemName {
+        let owned = |field: DisplayBytes<&[u8]>| DisplayBytes(field.as_bytes().to_vec());
+        OwnedSystemName {
+            sysname: owned(self.sysname()),
+            nodename: owned(self.nodename()),
+            release: owned(self.release()),
+            version: owned(self.version()),
+            machine: owned(self.machine()),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            domain: owned(self.domain()),
+        }
+    }
+    fn fields(&self) -> SystemNameFields<'_> {
+        [
+            ("sysname", self.sysname()),
+            ("nodename", self.nodename()),
+            ("release", self.release()),
+            ("version", self.version()),
+            ("machine", self.machine()),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ("domain", self.domain()),
+        ]
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+type SystemNameFields<'s> = [(&'static str, DisplayBytes<&'s [u8]>); 6];
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+type SystemNameFields<'s> = [(&'static str, DisplayBytes<&'s [u8]>); 5];
+
+/// An owned copy of a [`SystemName`], created by [`SystemName::into_owned`].
+///
+/// Formats and serializes the same as the [`SystemName`] it was copied from.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct OwnedSystemName {
+    pub sysname: DisplayBytes<Vec<u8>>,
+    pub nodename: DisplayBytes<Vec<u8>>,
+    pub release: DisplayBytes<Vec<u8>>,
+    pub version: DisplayBytes<Vec<u8>>,
+    pub machine: DisplayBytes<Vec<u8>>,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub domain: DisplayBytes<Vec<u8>>,
+}
+
+impl OwnedSystemName {
+    fn fields(&self) -> SystemNameFields<'_> {
+        fn field(field: &DisplayBytes<Vec<u8>>) -> DisplayBytes<&[u8]> {
+            DisplayBytes(field.as_bytes())
+        }
+        [
+            ("sysname", field(&self.sysname)),
+            ("nodename", field(&self.nodename)),
+            ("release", field(&self.release)),
+            ("version", field(&self.version)),
+            ("machine", field(&self.machine)),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ("domain", field(&self.domain)),
+        ]
+    }
+}
+
+fn debug_fields(f: &mut fmt::Formatter<'_>, fields: SystemNameFields<'_>) -> fmt::Result {
+    let mut debug = f.debug_struct("SystemName");
+    for (name, value) in &fields {
+        debug.field(name, value);
+    }
+    debug.finish()
+}
+
+/// Serializes the fields as strings, replacing invalid UTF-8 like [`DisplayBytes`] does.
+#[cfg(feature = "serde")]
+fn serialize_fields<S: serde::Serializer>(
+    serializer: S,
+    fields: SystemNameFields<'_>,
+) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeStruct;
+    let mut state = serializer.serialize_struct("SystemName", fields.len())?;
+    for (name, value) in &fields {
+        state.serialize_field(name, &value.to_string())?;
+    }
+    state.end()
+}
+
+impl fmt::Debug for OwnedSystemName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        debug_fields(f, self.fields())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SystemName {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_fields(serializer, self.fields())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for OwnedSystemName {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_fields(serializer, self.fields())
+    }
+}
+
+const MACHINE_ID_PATHS: [&str; 2] = ["/etc/machine-id", "/var/lib/dbus/machine-id"];
+
+/// The 32 hex digit identifier of this installation, stable across reboots.
+///
+/// Read from `/etc/machine-id`, falling back to the D-Bus copy in `/var/lib/dbus/machine-id`
+/// when it's missing, empty or `uninitialized`, as systemd leaves it before the first boot.
+pub fn machine_id() -> io::Result<DisplayBytes<ArrayVec<u8, 32>>> {
+    machine_id_from(&MACHINE_ID_PATHS)
+}
+
+fn machine_id_from(paths: &[impl AsRef<Path>]) -> io::Result<DisplayBytes<ArrayVec<u8, 32>>> {
+    let mut last_err = None;
+    for path in paths {
+        match fs::read(path).and_then(|contents| parse_machine_id(&contents)) {
+            Ok(id) => return Ok(id),
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| io::ErrorKind::NotFound.into()))
+}
+
+fn parse_machine_id(contents: &[u8]) -> io::Result<DisplayBytes<ArrayVec<u8, 32>>> {
+    let id = contents.strip_suffix(b"\n").unwrap_or(contents);
+    if id.len() != 32 || !id.iter().all(|&digit| unhex(digit).is_some()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "machine-id isn't 32 hex digits",
+        ));
+    }
+    Ok(DisplayBytes(id.iter().copied().collect()))
+}
+
+impl fmt::Debug for SystemName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        debug_fields(f, self.fields())
+    }
+}
+
+/// Storage for the strings `getpwuid_r` writes alongside the `passwd` entry.
+pub trait BackingBuffer: AsRef<[u8]> + AsMut<[u8]> {
+    /// Enlarges the buffer to at most `max_len` bytes, returning `false` if it can't grow
+    /// any further.
+    fn grow(&mut self, _max_len: usize) -> bool {
+        false
+    }
+}
+
+impl<const N: usize> BackingBuffer for [u8; N] {}
+impl BackingBuffer for &mut [u8] {}
+
+/// A heap buffer that doubles its size every time it's grown, up to [`GrowableBackingBuffer::MAX_LEN`].
+#[derive(Debug, Clone, Default)]
+pub struct GrowableBackingBuffer(Vec<u8>);
+
+impl GrowableBackingBuffer {
+    /// Used when the system doesn't suggest a size for `passwd` entries.
+    pub const DEFAULT_PASSWD_LEN: usize = 1024;
+    /// The buffer won't grow past this, so a misbehaving NSS backend can't make us allocate forever.
+    pub const MAX_LEN: usize = 1 << 20;
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(vec![0; capacity])
+    }
+    /// A buffer sized by `sysconf(_SC_GETPW_R_SIZE_MAX)`, falling back to
+    /// [`GrowableBackingBuffer::DEFAULT_PASSWD_LEN`] when there's no hint.
+    pub fn for_passwd() -> Self {
+        // SAFETY: sysconf has no preconditions
+        let hint = unsafe { libc::sysconf(libc::_SC_GETPW_R_SIZE_MAX) };
+        let capacity = usize::try_from(hint)
+            .ok()
+            .filter(|&len| len != 0)
+            .unwrap_or(Self::DEFAULT_PASSWD_LEN);
+        Self::with_capacity(capacity.min(Self::MAX_LEN))
+    }
+}
+
+impl AsRef<[u8]> for GrowableBackingBuffer {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsMut<[u8]> for GrowableBackingBuffer {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl BackingBuffer for GrowableBackingBuffer {
+    fn grow(&mut self, max_len: usize) -> bool {
+        let new_len = (self.0.len() * 2).clamp(128, Self::MAX_LEN).min(max_len);
+        if new_len <= self.0.len() {
+            return false;
+        }
+        self.0.resize(new_len, 0);
+        true
+    }
+}
+
+#[derive(Debug)]
+pub enum PwuIdError {
+    /// No user with the requested uid exists.
+    NotFound,
+    /// The backing buffer couldn't hold the entry and couldn't be grown.
+    BufferTooSmall,
+    IOError(std::io::Error),
+}
+
+impl fmt::Display for PwuIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PwuIdError::NotFound => f.write_str("no user with the requested uid"),
+            PwuIdError::BufferTooSmall => f.write_str("passwd entry didn't fit in the buffer"),
+            PwuIdError::IOError(err) => write!(f, "failed to read passwd entry: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PwuIdError {}
+
+/// A `passwd` database entry, as returned by `getpwuid_r`.
+///
+/// String fields are stored as ranges into the owned backing buffer rather than the pointers
+/// `getpwuid_r` returns, so the entry stays valid when moved. That also makes it `Send` and
+/// `Sync` whenever `B` is, without any unsafe impls.
+pub struct PwuId<B> {
+    buf: B,
+    uid: u32,
+    gid: u32,
+    name: Range<usize>,
+    passwd: Range<usize>,
+    gecos: Range<usize>,
+    dir: Range<usize>,
+    shell: Range<usize>,
+}
+
+impl PwuId<GrowableBackingBuffer> {
+    /// Looks up `uid`, allocating a buffer that grows as needed.
+    pub fn get_alloc(uid: u32) -> Result<Self, PwuIdError> {
+        Self::get(GrowableBackingBuffer::for_passwd(), uid)
+    }
+}
+
+impl<B: BackingBuffer> PwuId<B> {
+    const MAX_ATTEMPTS: usize = 32;
+
+    /// Looks up `uid`, growing `buf` and retrying while it's too small.
+    pub fn get(buf: B, uid: u32) -> Result<Self, PwuIdError> {
+        Self::get_with_limit(buf, uid, Self::MAX_ATTEMPTS, GrowableBackingBuffer::MAX_LEN)
+    }
+    /// Like [`Self::get`], but gives up with [`PwuIdError::BufferTooSmall`] after `max_attempts`
+    /// lookups, or once a lookup with a buffer of `max_capacity` bytes has failed.
+    pub fn get_with_limit(
+        mut buf: B,
+        uid: u32,
+        max_attempts: usize,
+        max_capacity: usize,
+    ) -> Result<Self, PwuIdError> {
+        for _ in 0..max_attempts {
+            match Self::try_get(buf, uid) {
+                Err((PwuIdError::BufferTooSmall, mut returned)) => {
+                    // The last growth is clamped to max_capacity, so it gets tried too
+                    if !returned.grow(max_capacity) {
+                        return Err(PwuIdError::BufferTooSmall);
+                    }
+                    buf = returned;
+                }
+                res => return res.map_err(|(err, _)| err),
+            }
+        }
+        Err(PwuIdError::BufferTooSmall)
+    }
+    /// Looks up `uid` with a single call, handing the buffer back on failure.
+    pub fn try_get(mut buf: B, uid: u32) -> Result<Self, (PwuIdError, B)> {
+        let mut entry = MaybeUninit::<libc::passwd>::uninit();
+        let mut result = std::ptr::null_mut();
+        let bytes = buf.as_mut();
+        let base = bytes.as_ptr() as usize;
+        // SAFETY: all pointers are valid for the duration of the call and the length matches the buffer
+        let code = unsafe {
+            libc::getpwuid_r(
+                uid,
+                entry.as_mut_ptr(),
+                bytes.as_mut_ptr().cast(),
+                bytes.len(),
+                &mut result,
+            )
+        };
+        match code {
+            0 if result.is_null() => return Err((PwuIdError::NotFound, buf)),
+            0 => (),
+            libc::ERANGE => return Err((PwuIdError::BufferTooSmall, buf)),
+            code => {
+                return Err((
+                    PwuIdError::IOError(std::io::Error::from_raw_os_error(code)),
+                    buf,
+                ))
+            }
+        }
+        // SAFETY: getpwuid_r succeeded, so the entry is initialized
+        let entry = unsafe { entry.assume_init() };
+        // SAFETY: all string fields are null or NUL-terminated strings inside `buf`
+        let range = |ptr: *mut c_char| unsafe {
+            if ptr.is_null() {
+                return 0..0;
+            }
+            let start = ptr as usize - base;
+            start..start + CStr::from_ptr(ptr).count_bytes()
+        };
+        Ok(Self {
+            uid: entry.pw_uid,
+            gid: entry.pw_gid,
+            name: range(entry.pw_name),
+            passwd: range(entry.pw_passwd),
+            gecos: range(entry.pw_gecos),
+            dir: range(entry.pw_dir),
+            shell: range(entry.pw_shell),
+            buf,
+        })
+    }
+    fn field(&self, range: &Range<usize>) -> &[u8] {
+        &self.buf.as_ref()[range.clone()]
+    }
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+    pub fn gid(&self) -> u32 {
+        self.gid
+    }
+    pub fn name(&self) -> DisplayBytes<&[u8]> {
+        DisplayBytes(self.field(&self.name))
+    }
+    pub fn passwd(&self) -> DisplayBytes<&[u8]> {
+        DisplayBytes(self.field(&self.passwd))
+    }
+    pub fn gecos(&self) -> DisplayBytes<&[u8]> {
+        DisplayBytes(self.field(&self.gecos))
+    }
+    pub fn home(&self) -> DisplayBytes<&[u8]> {
+        DisplayBytes(self.field(&self.dir))
+    }
+    pub fn home_path(&self) -> &Path {
+        Path::new(OsStr::from_bytes(self.field(&self.dir)))
+    }
+    pub fn shell(&self) -> DisplayBytes<&[u8]> {
+        DisplayBytes(self.field(&self.shell))
+    }
+}
+
+impl<B: BackingBuffer> fmt::Debug for PwuId<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PwuId")
+            .field("uid", &self.uid)
+            .field("gid", &self.gid)
+            .field("name", &self.name())
+            .field("gecos", &self.gecos())
+            .field("home", &self.home())
+            .field("shell", &self.shell())
+            .finish()
+    }
+}
+
+/// Memoizes uid to name lookups, evicting the least recently used entry once full.
+///
+/// The cache owns the looked up entries, so returned names stay valid until the next lookup.
+/// `N` must be at least 1.
+pub struct PwuIdCache<const N: usize = 8> {
+    // Most recently used entries are at the back
+    entries: ArrayVec<PwuId<GrowableBackingBuffer>, N>,
+}
+
+impl<const N: usize> PwuIdCache<N> {
+    pub const fn new() -> Self {
+        const { assert!(N > 0, "a PwuIdCache needs room for at least one entry") };
+        Self {
+            entries: ArrayVec::new(),
+        }
+    }
+    /// The name of the user with `uid`, or `None` if there's no such user.
+    pub fn name_of(&mut self, uid: u32) -> Option<DisplayBytes<&[u8]>> {
+        match self.entries.iter().position(|entry| entry.uid() == uid) {
+            Some(idx) => {
+                let entry = self.entries.remove(idx);
+                self.entries.push(entry);
+            }
+            None => {
+                let entry = PwuId::get_alloc(uid).ok()?;
+                if self.entries.is_full() {
+                    self.entries.remove(0);
+                }
+                self.entries.push(entry);
+            }
+        }
+        self.entries.last().map(PwuId::name)
+    }
+}
+
+impl<const N: usize> Default for PwuIdCache<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn pwuid_across_threads() {
+        assert_send_sync::<PwuId<GrowableBackingBuffer>>();
+        assert_send_sync::<PwuId<[u8; 1024]>>();
+        assert_send_sync::<OwnedSystemName>();
+
+        let Ok(entry) = PwuId::get_alloc(current_uid()) else {
+            // Some sandboxes have no passwd entry for the current user
+            return;
+        };
+        let name = entry.name().to_string();
+        let home = entry.home_path().to_path_buf();
+        // The buffer moves with the entry, and the fields still point into it
+        let (moved_name, moved_home) =
+            std::thread::spawn(move || (entry.name().to_string(), entry.home_path().to_path_buf()))
+                .join()
+                .unwrap();
+        assert_eq!((moved_name, moved_home), (name, home));
+    }
+
+    #[test]
+    fn cache_evicts_least_recent() {
+        let mut cache = PwuIdCache::<1>::new();
+        assert_eq!(cache.name_of(0).unwrap().to_string(), "root");
+        assert_eq!(cache.name_of(0).unwrap().to_string(), "root");
+        assert!(cache.name_of(u32::MAX - 1).is_none());
+        assert_eq!(cache.entries.len(), 1);
+
+        let uids: Vec<_> = (0..1000)
+            .filter(|&uid| PwuId::get_alloc(uid).is_ok())
+            .take(3)
+            .collect();
+        let [a, b, c] = uids[..] else {
+            eprintln!("skipping the eviction order check, fewer than 3 users in passwd");
+            return;
+        };
+        let mut cache = PwuIdCache::<2>::new();
+        cache.name_of(a).unwrap();
+        cache.name_of(b).unwrap();
+        // Touching `a` leaves `b` as the least recently used
+        cache.name_of(a).unwrap();
+        cache.name_of(c).unwrap();
+        let cached: Vec<_> = cache.entries.iter().map(PwuId::uid).collect();
+        assert_eq!(cached, [a, c]);
+    }
+
+    #[test]
+    fn empty_fields() {
+        // SAFETY: utsname is plain data, for which all zeroes is valid
+        let empty = SystemName(unsafe { std::mem::zeroed() });
+        assert!(empty.release().is_empty());
+        assert!(empty.release_opt().is_none());
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        assert!(empty.domain_opt().is_none());
+        let name = SystemName::get();
+        assert_eq!(
+            name.sysname_opt().map(|name| name.to_string()),
+            Some(name.sysname().to_string())
+        );
+    }
+
+    #[test]
+    fn owned_snapshot() {
+        let name = SystemName::get();
+        let owned = name.into_owned();
+        assert_eq!(owned.release.to_string(), name.release().to_string());
+        assert_eq!(format!("{owned:?}"), format!("{name:?}"));
+        // Owned fields can leave the thread the name was read on
+        let handle = std::thread::spawn(move || owned.nodename.to_string());
+        assert_eq!(handle.join().unwrap(), name.nodename().to_string());
+    }
+
+    #[test]
+    fn machine_id() {
+        let id = parse_machine_id(b"4f0c3e8a2b1d4c6e9f7a5b3c1d2e4f60\n").unwrap();
+        assert_eq!(id.to_string(), "4f0c3e8a2b1d4c6e9f7a5b3c1d2e4f60");
+        assert!(parse_machine_id(b"4f0c3e8a2b1d4c6e9f7a5b3c1d2e4f6\n").is_err());
+        assert!(parse_machine_id(b"uninitialized\n").is_err());
+        assert!(parse_machine_id(b"").is_err());
+
+        let dir = TempDir::new("machine-id");
+        let (etc, dbus) = (dir.join("etc"), dir.join("dbus"));
+        fs::write(&dbus, "4f0c3e8a2b1d4c6e9f7a5b3c1d2e4f60\n").unwrap();
+        for uninitialized in ["", "uninitialized\n"] {
+            fs::write(&etc, uninitialized).unwrap();
+            let id = machine_id_from(&[&etc, &dbus]).unwrap();
+            assert_eq!(id.as_bytes(), b"4f0c3e8a2b1d4c6e9f7a5b3c1d2e4f60");
+        }
+        // Without a valid copy either, the last error is reported
+        fs::write(&dbus, "").unwrap();
+        let err = machine_id_from(&[&etc, &dbus]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn lookup_limits() {
+        let buf = || GrowableBackingBuffer::with_capacity(1);
+        assert!(matches!(
+            PwuId::get_with_limit(buf(), 0, 1, 1 << 16),
+            Err(PwuIdError::BufferTooSmall)
+        ));
+        assert!(matches!(
+            PwuId::get_with_limit(buf(), 0, 32, 8),
+            Err(PwuIdError::BufferTooSmall)
+        ));
+        let root = PwuId::get_with_limit(buf(), 0, 32, 1 << 16).unwrap();
+        assert_eq!(root.name().to_string(), "root");
+        // Doubling from 1 skips straight past 100, the cap itself is still tried
+        let root = PwuId::get_with_limit(buf(), 0, 32, 100).unwrap();
+        assert_eq!(root.name().to_string(), "root");
+    }
+
+    #[test]
+    fn growth_is_capped() {
+        let mut buf = GrowableBackingBuffer::with_capacity(100);
+        assert!(buf.grow(usize::MAX));
+        assert_eq!(buf.as_ref().len(), 200);
+        while buf.grow(usize::MAX) {}
+        assert_eq!(buf.as_ref().len(), GrowableBackingBuffer::MAX_LEN);
+
+        // A cap that isn't a power of two is reached exactly, and not passed
+        let mut buf = GrowableBackingBuffer::with_capacity(600);
+        assert!(buf.grow(1000));
+        assert_eq!(buf.as_ref().len(), 1000);
+        assert!(!buf.grow(1000));
+        assert_eq!(buf.as_ref().len(), 1000);
+        assert!(
+            GrowableBackingBuffer::for_passwd().as_ref().len() <= GrowableBackingBuffer::MAX_LEN
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Network interfaces and the local address of the one holding the default route.
+use crate::{arrayvec::ArrayVec, display::DisplayBytes, parse::sysfs_decimal};
+use std::{
+    ffi::CStr,
+    fs::{self, ReadDir},
+    io,
+    net::{IpAddr, Ipv4Addr},
+    os::unix::ffi::OsStrExt,
+    path::PathBuf,
+};
+
+const PROC_ROUTE: &str = "/proc/net/route";
+const SYSFS_NET: &str = "/sys/class/net";
+/// `ARPHRD_LOOPBACK`, the `type` of loopback interfaces.
+const ARPHRD_LOOPBACK: u16 = 772;
+
+/// Interface names are at most `IFNAMSIZ - 1` bytes long.
+pub type InterfaceName = DisplayBytes<ArrayVec<u8, 16>>;
+
+/// The interface with the default route and its IPv4 address, e.g. `(wlan0, 192.168.1.20)`.
+///
+/// `None` when there's no default route, i.e. the system is offline.
+pub fn primary_interface() -> Option<(InterfaceName, IpAddr)> {
+    let routes = fs::read(PROC_ROUTE).ok()?;
+    let name = default_route(&routes)?;
+    let addr = interface_ipv4(name)?;
+    Some((
+        DisplayBytes(name.iter().copied().collect()),
+        IpAddr::V4(addr),
+    ))
+}
+
+/// The local IP address, see [`primary_interface`].
+pub fn local_ip() -> Option<IpAddr> {
+    primary_interface().map(|(_, addr)| addr)
+}
+
+/// What kind of link an interface is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterfaceKind {
+    Ethernet,
+    Wireless,
+    Loopback,
+    /// Not backed by hardware, e.g. bridges, VPN tunnels and container veths.
+    Virtual,
+}
+
+/// The RFC 2863 operational state, from `/sys/class/net/<if>/operstate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperState {
+    Unknown,
+    NotPresent,
+    Down,
+    LowerLayerDown,
+    Testing,
+    Dormant,
+    Up,
+}
+
+impl OperState {
+    fn parse(state: &[u8]) -> Self {
+        match state.trim_ascii() {
+            b"notpresent" => OperState::NotPresent,
+            b"down" => OperState::Down,
+            b"lowerlayerdown" => OperState::LowerLayerDown,
+            b"testing" => OperState::Testing,
+            b"dormant" => OperState::Dormant,
+            b"up" => OperState::Up,
+            _ => OperState::Unknown,
+        }
+    }
+}
+
+/// Every network interface, yielding `(name, kind, operstate)`.
+///
+/// Loopback interfaces are skipped unless enabled with [`Interfaces::include_loopback`].
+pub fn interfaces() -> io::Result<Interfaces> {
+    interfaces_at(SYSFS_NET)
+}
+
+/// Like [`interfaces`], looking under `root` instead of `/sys/class/net`.
+pub fn interfaces_at(root: impl Into<PathBuf>) -> io::Result<Interfaces> {
+    Ok(Interfaces {
+        dir: fs::read_dir(root.into())?,
+        loopback: false,
+    })
+}
+
+/// Created by [`interfaces`].
+pub struct Interfaces {
+    dir: ReadDir,
+    loopback: bool,
+}
+
+impl Interfaces {
+    pub fn include_loopback(mut self, include: bool) -> Self {
+        self.loopback = include;
+        self
+    }
+}
+
+impl Iterator for Interfaces {
+    type Item = (InterfaceName, InterfaceKind, OperState);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Ok(entry) = self.dir.next()? else {
+                continue;
+            };
+            let path = entry.path();
+            let arp_type = fs::read(path.join("type"))
+                .ok()
+                .and_then(|kind| sysfs_decimal::<u16>(&kind));
+            let kind = if arp_type == Some(ARPHRD_LOOPBACK) {
+                InterfaceKind::Loopback
+            } else if path.join("wireless").exists() || path.join("phy80211").exists() {
+                InterfaceKind::Wireless
+            } else if path.join("device").exists() {
+                InterfaceKind::Ethernet
+            } else {
+                InterfaceKind::Virtual
+            };
+            if kind == InterfaceKind::Loopback && !self.loopback {
+                continue;
+            }
+            let state = fs::read(path.join("operstate"))
+                .map_or(OperState::Unknown, |state| OperState::parse(&state));
+            let name = entry.file_name().as_bytes().iter().copied().collect();
+            return Some((DisplayBytes(name), kind, state));
+        }
+    }
+}
+
+/// Finds the interface of the default route with the lowest metric in `/proc/net/route`.
+fn default_route(routes: &[u8]) -> Option<&[u8]> {
+    routes
+        .split(|&b| b == b'\n')
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line
+                .split(u8::is_ascii_whitespace)
+                .filter(|field| !field.is_empty());
+            let iface = fields.next()?;
+            let destination = fields.next()?;
+            let metric = fields.nth(4)?;
+            let mask = fields.next()?;
+            let is_default = destination == b"00000000" && mask == b"00000000";
+            let metric: u32 = std::str::from_utf8(metric).ok()?.parse().ok()?;
+            is_default.then_some((metric, iface))
+        })
+        .min_by_key(|&(metric, _)| metric)
+        .map(|(_, iface)| iface)
+}
+
+/// The first IPv4 address assigned to the interface called `name`.
+fn interface_ipv4(name: &[u8]) -> Option<Ipv4Addr> {
+    let mut addrs = std::ptr::null_mut();
+    // SAFETY: getifaddrs only writes the list head on success
+    if unsafe { libc::getifaddrs(&mut addrs) } != 0 {
+        return None;
+    }
+    let mut found = None;
+    let mut cur = addrs;
+    // SAFETY: every node is either null, ending the list, or valid until freeifaddrs below
+    while let Some(ifa) = unsafe { cur.as_ref() } {
+        cur = ifa.ifa_next;
+        // SAFETY: ifa_addr is either null or points to a sockaddr owned by the list
+        let Some(addr) = (unsafe { ifa.ifa_addr.as_ref() }) else {
+            continue;
+        };
+        if i32::from(addr.sa_family) != libc::AF_INET
+            // SAFETY: ifa_name is a NUL terminated string owned by the list
+            || unsafe { CStr::from_ptr(ifa.ifa_name) }.to_bytes() != name
+        {
+            continue;
+        }
+        // SAFETY: a sockaddr of family AF_INET is the start of a sockaddr_in
+        let addr = unsafe { &*ifa.ifa_addr.cast::<libc::sockaddr_in>() };
+        found = Some(Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)));
+        break;
+    }
+    // SAFETY: addrs came from a successful getifaddrs call and isn't used afterwards
+    unsafe { libc::freeifaddrs(addrs) };
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn sysfs_interfaces() {
+        let root = TempDir::new("net");
+        for (name, arp_type, state, extra) in [
+            ("lo", "772\n", "unknown\n", None),
+            ("eth0", "1\n", "up\n", Some("device")),
+            ("wlan0", "1\n", "dormant\n", Some("wireless")),
+            ("br0", "1\n", "down\n", None),
+        ] {
+            let dir = root.join(name);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("type"), arp_type).unwrap();
+            fs::write(dir.join("operstate"), state).unwrap();
+            if let Some(extra) = extra {
+                fs::create_dir(dir.join(extra)).unwrap();
+            }
+        }
+
+        let mut found: Vec<_> = interfaces_at(&root)
+            .unwrap()
+            .map(|(name, kind, state)| (name.to_string(), kind, state))
+            .collect();
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            found,
+            [
+                ("br0".to_string(), InterfaceKind::Virtual, OperState::Down),
+                ("eth0".to_string(), InterfaceKind::Ethernet, OperState::Up),
+                (
+                    "wlan0".to_string(),
+                    InterfaceKind::Wireless,
+                    OperState::Dormant
+                ),
+            ]
+        );
+        let with_lo = interfaces_at(&root).unwrap().include_loopback(true);
+        assert!(with_lo
+            .into_iter()
+            .any(|(_, kind, _)| kind == InterfaceKind::Loopback));
+    }
+
+    #[test]
+    fn lowest_metric_default_route() {
+        let routes = b"Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+            wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0\n\
+            eth0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0\n\
+            eth0\t0001A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0\n";
+        assert_eq!(default_route(routes), Some(&b"eth0"[..]));
+        assert_eq!(default_route(&routes[..routes.len() / 3]), None);
+        // Sandboxes may leave the loopback interface without an address
+        if let Some(addr) = interface_ipv4(b"lo") {
+            assert!(addr.is_loopback());
+        }
+        assert_eq!(interface_ipv4(b"rxfetch-missing0"), None);
+    }
+}
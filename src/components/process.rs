@@ -0,0 +1,136 @@
+//! Process counts and well-known processes, from the numeric directories in `/proc`.
+use crate::{display::DisplayBytes, parse::sysfs_decimal};
+use std::{fs, os::unix::ffi::OsStrExt, path::Path};
+
+const PROC: &str = "/proc";
+
+/// The `comm` of display managers, as the kernel truncates it to 15 bytes.
+const DISPLAY_MANAGERS: &[&[u8]] = &[
+    b"gdm", b"gdm3", b"sddm", b"lightdm", b"ly", b"ly-dm", b"lxdm", b"xdm", b"greetd",
+];
+
+/// The number of processes, and with `count_running` how many of them are running.
+///
+/// Finding the running ones reads every process's `stat`, so it costs a file read per
+/// process. Processes that exit during the scan aren't counted as running.
+/// `(0, None)` if `/proc` can't be read.
+pub fn process_count(count_running: bool) -> (usize, Option<usize>) {
+    process_count_in(Path::new(PROC), count_running)
+}
+
+fn process_count_in(root: &Path, count_running: bool) -> (usize, Option<usize>) {
+    let Some(processes) = processes_in(root) else {
+        return (0, None);
+    };
+    let mut total = 0;
+    let mut running = 0;
+    for entry in processes {
+        total += 1;
+        if count_running {
+            let stat = fs::read(entry.path().join("stat")).unwrap_or_default();
+            running += usize::from(stat_state(&stat) == Some(b'R'));
+        }
+    }
+    (total, count_running.then_some(running))
+}
+
+/// The display manager running on this system, e.g. `sddm`.
+///
+/// Found by looking for known display managers among the `comm` of every process.
+/// `None` when the system was started from a TTY without one.
+pub fn display_manager() -> Option<DisplayBytes<Vec<u8>>> {
+    display_manager_in(Path::new(PROC))
+}
+
+fn display_manager_in(root: &Path) -> Option<DisplayBytes<Vec<u8>>> {
+    processes_in(root)?.find_map(|entry| {
+        let comm = fs::read(entry.path().join("comm")).ok()?;
+        let name = comm.trim_ascii_end();
+        DISPLAY_MANAGERS
+            .contains(&name)
+            .then(|| DisplayBytes(name.to_vec()))
+    })
+}
+
+/// The `/proc/<pid>` directory of every process, or `None` if `root` can't be read.
+fn processes_in(root: &Path) -> Option<impl Iterator<Item = fs::DirEntry>> {
+    let dir = fs::read_dir(root).ok()?;
+    Some(
+        dir.flatten()
+            .filter(|entry| sysfs_decimal::<u32>(entry.file_name().as_bytes()).is_some()),
+    )
+}
+
+/// The state field of `/proc/<pid>/stat`, e.g. `R` or `S`.
+///
+/// The command name before it is parenthesized but may itself contain spaces and parentheses,
+/// so the state is found after the *last* `)`.
+fn stat_state(stat: &[u8]) -> Option<u8> {
+    let end = stat.iter().rposition(|&byte| byte == b')')?;
+    match stat[end + 1..] {
+        [b' ', state, ..] => Some(state),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn state() {
+        assert_eq!(stat_state(b"1 (systemd) S 0 1 1 0"), Some(b'S'));
+        assert_eq!(stat_state(b"42 (a) b) (c) R 1 42"), Some(b'R'));
+        assert_eq!(stat_state(b"42 (unterminated R 1"), None);
+        assert_eq!(stat_state(b"42 (trailing)"), None);
+    }
+
+    #[test]
+    fn counts() {
+        let root = TempDir::new("proc");
+        for (pid, state) in [("1", "S"), ("20", "R"), ("300", "R")] {
+            fs::create_dir_all(root.join(pid)).unwrap();
+            fs::write(
+                root.join(pid).join("stat"),
+                format!("{pid} (worker) {state} 1"),
+            )
+            .unwrap();
+        }
+        // Exited before its stat was read
+        fs::create_dir_all(root.join("4000")).unwrap();
+        fs::create_dir_all(root.join("self")).unwrap();
+        fs::create_dir_all(root.join("sys")).unwrap();
+
+        assert_eq!(process_count_in(&root, false), (4, None));
+        assert_eq!(process_count_in(&root, true), (4, Some(2)));
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(process_count_in(&root, true), (0, None));
+    }
+
+    #[test]
+    fn display_managers() {
+        let root = TempDir::new("dm");
+        for (pid, comm) in [("1", "systemd\n"), ("812", "bash\n")] {
+            fs::create_dir_all(root.join(pid)).unwrap();
+            fs::write(root.join(pid).join("comm"), comm).unwrap();
+        }
+        assert_eq!(display_manager_in(&root), None);
+
+        // A prefix of a display manager's name doesn't count
+        fs::create_dir_all(root.join("901")).unwrap();
+        fs::write(root.join("901/comm"), "lyx\n").unwrap();
+        assert_eq!(display_manager_in(&root), None);
+
+        fs::create_dir_all(root.join("950")).unwrap();
+        fs::write(root.join("950/comm"), "sddm\n").unwrap();
+        assert_eq!(
+            display_manager_in(&root)
+                .as_ref()
+                .map(DisplayBytes::as_bytes),
+            Some(&b"sddm"[..])
+        );
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(display_manager_in(&root), None);
+    }
+}
@@ -0,0 +1,49 @@
+//! The kernel's entropy pool and hardware random number generator.
+use crate::{
+    display::DisplayBytes,
+    parse::{sysfs_decimal, trim_text},
+};
+use std::fs;
+
+const ENTROPY_AVAIL: &str = "/proc/sys/kernel/random/entropy_avail";
+const POOL_SIZE: &str = "/proc/sys/kernel/random/poolsize";
+const RNG_CURRENT: &str = "/sys/devices/virtual/misc/hw_random/rng_current";
+
+/// The entropy available in the pool, in bits.
+///
+/// Since Linux 5.18 the pool no longer depletes, so this stays at [`pool_size`].
+pub fn entropy() -> Option<u32> {
+    sysfs_decimal(&fs::read(ENTROPY_AVAIL).ok()?)
+}
+
+/// The size of the entropy pool, in bits.
+pub fn pool_size() -> Option<u32> {
+    sysfs_decimal(&fs::read(POOL_SIZE).ok()?)
+}
+
+/// The hardware RNG feeding the pool, e.g. `tpm-rng-0` or `virtio_rng.0`.
+///
+/// `None` without a hardware RNG, which the kernel reports as `none`.
+pub fn rng_current() -> Option<DisplayBytes<Vec<u8>>> {
+    rng_name(fs::read(RNG_CURRENT).ok()?)
+}
+
+fn rng_name(name: Vec<u8>) -> Option<DisplayBytes<Vec<u8>>> {
+    let name = trim_text(name);
+    (!name.is_empty() && name.as_bytes() != b"none").then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng() {
+        assert_eq!(
+            rng_name(b"tpm-rng-0\n".to_vec()).unwrap().to_string(),
+            "tpm-rng-0"
+        );
+        assert!(rng_name(b"none\n".to_vec()).is_none());
+        assert!(rng_name(Vec::new()).is_none());
+    }
+}
@@ -0,0 +1,141 @@
+//! The display protocol of the current session.
+use std::{
+    env,
+    ffi::OsStr,
+    fmt, fs,
+    os::unix::fs::FileTypeExt,
+    path::{Path, PathBuf},
+};
+
+const X11_SOCKET_DIR: &str = "/tmp/.X11-unix";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionProtocol {
+    Wayland,
+    X11,
+    /// Neither display server could be found, e.g. on a virtual console or over SSH.
+    Tty,
+}
+
+impl fmt::Display for SessionProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SessionProtocol::Wayland => "Wayland",
+            SessionProtocol::X11 => "X11",
+            SessionProtocol::Tty => "TTY",
+        })
+    }
+}
+
+/// Finds the display protocol by looking for the sockets that `WAYLAND_DISPLAY` and `DISPLAY` point to.
+///
+/// The variables alone aren't trusted, as they're often inherited stale by nested sessions,
+/// so a variable without a live socket behind it is ignored. Wayland wins when both are present,
+/// since XWayland sets `DISPLAY` too.
+pub fn session_protocol() -> SessionProtocol {
+    let runtime_dir = env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+    let wayland_display = env::var_os("WAYLAND_DISPLAY");
+    let display = env::var_os("DISPLAY");
+    protocol_from(
+        runtime_dir.as_deref(),
+        wayland_display.as_deref(),
+        display.as_deref(),
+        Path::new(X11_SOCKET_DIR),
+    )
+}
+
+fn protocol_from(
+    runtime_dir: Option<&Path>,
+    wayland_display: Option<&OsStr>,
+    display: Option<&OsStr>,
+    x11_dir: &Path,
+) -> SessionProtocol {
+    if let Some(socket) = wayland_socket(runtime_dir, wayland_display) {
+        if is_socket(&socket) {
+            return SessionProtocol::Wayland;
+        }
+    }
+    if let Some(socket) = display.and_then(|display| x11_socket(x11_dir, display)) {
+        if is_socket(&socket) {
+            return SessionProtocol::X11;
+        }
+    }
+    SessionProtocol::Tty
+}
+
+/// `WAYLAND_DISPLAY` is either an absolute path or a name relative to `XDG_RUNTIME_DIR`.
+fn wayland_socket(runtime_dir: Option<&Path>, wayland_display: Option<&OsStr>) -> Option<PathBuf> {
+    let wayland_display = Path::new(wayland_display.filter(|display| !display.is_empty())?);
+    if wayland_display.is_absolute() {
+        return Some(wayland_display.to_path_buf());
+    }
+    Some(runtime_dir?.join(wayland_display))
+}
+
+/// Maps a local `DISPLAY` such as `:0` or `:1.0` to its socket, e.g. `/tmp/.X11-unix/X0`.
+///
+/// Remote displays like `localhost:10.0` have no local socket.
+fn x11_socket(x11_dir: &Path, display: &OsStr) -> Option<PathBuf> {
+    let display = display.as_encoded_bytes().strip_prefix(b":")?;
+    let number = display.split(|&b| b == b'.').next()?;
+    if number.is_empty() || !number.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let mut socket = OsStr::new("X").to_os_string();
+    // Only ASCII digits are left, so this is valid UTF-8
+    socket.push(std::str::from_utf8(number).ok()?);
+    Some(x11_dir.join(socket))
+}
+
+fn is_socket(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|meta| meta.file_type().is_socket())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn sockets() {
+        let root = TempDir::new("session");
+        let runtime_dir = root.join("run");
+        let x11_dir = root.join("X11");
+        fs::create_dir_all(&runtime_dir).unwrap();
+        fs::create_dir_all(&x11_dir).unwrap();
+        let protocol = |wayland: Option<&str>, display: Option<&str>| {
+            protocol_from(
+                Some(&runtime_dir),
+                wayland.map(OsStr::new),
+                display.map(OsStr::new),
+                &x11_dir,
+            )
+        };
+
+        // Stale variables without sockets
+        assert_eq!(
+            protocol(Some("wayland-1"), Some(":1")),
+            SessionProtocol::Tty
+        );
+
+        let _x11 = UnixListener::bind(x11_dir.join("X1")).unwrap();
+        assert_eq!(
+            protocol(Some("wayland-1"), Some(":1.0")),
+            SessionProtocol::X11
+        );
+        assert_eq!(protocol(None, Some("localhost:1")), SessionProtocol::Tty);
+
+        let _wayland = UnixListener::bind(runtime_dir.join("wayland-1")).unwrap();
+        assert_eq!(
+            protocol(Some("wayland-1"), Some(":1")),
+            SessionProtocol::Wayland
+        );
+        let absolute = runtime_dir.join("wayland-1");
+        assert_eq!(protocol(absolute.to_str(), None), SessionProtocol::Wayland);
+
+        // A regular file isn't a live socket
+        fs::write(runtime_dir.join("wayland-2"), b"").unwrap();
+        assert_eq!(protocol(Some("wayland-2"), None), SessionProtocol::Tty);
+    }
+}
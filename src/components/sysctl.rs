@@ -0,0 +1,49 @@
+//! Kernel tunables from `/proc/sys`, as shown by `sysctl`.
+use crate::{display::DisplayBytes, parse::trim_text};
+use std::{fs, path::Path};
+
+const PROC_SYS: &str = "/proc/sys";
+
+/// Reads a tunable by its dotted key, e.g. `vm.swappiness` from `/proc/sys/vm/swappiness`.
+///
+/// The value has its trailing newline removed. `None` if the key doesn't exist,
+/// can't be read, or contains empty or `..` components.
+pub fn read_sysctl(key: &str) -> Option<DisplayBytes<Vec<u8>>> {
+    read_sysctl_in(Path::new(PROC_SYS), key)
+}
+
+fn read_sysctl_in(root: &Path, key: &str) -> Option<DisplayBytes<Vec<u8>>> {
+    let mut path = root.to_path_buf();
+    for part in key.split('.') {
+        if part.is_empty() || part.contains('/') {
+            return None;
+        }
+        path.push(part);
+    }
+    Some(trim_text(fs::read(path).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn dotted_keys() {
+        let root = TempDir::new("sysctl");
+        fs::create_dir_all(root.join("vm")).unwrap();
+        fs::write(root.join("vm/swappiness"), "60\n").unwrap();
+        fs::write(root.join("secret"), "hidden\n").unwrap();
+
+        assert_eq!(
+            read_sysctl_in(&root, "vm.swappiness").unwrap().to_string(),
+            "60"
+        );
+        assert!(read_sysctl_in(&root, "vm.max_map_count").is_none());
+        // A directory isn't a value
+        assert!(read_sysctl_in(&root, "vm").is_none());
+        // `..` has an empty component between its dots, and slashes aren't separators
+        assert!(read_sysctl_in(&root.join("vm"), "..secret").is_none());
+        assert!(read_sysctl_in(&root, "vm/swappiness").is_none());
+    }
+}
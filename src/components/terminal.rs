@@ -0,0 +1,130 @@
+//! The terminal emulator and the font it's configured with.
+use crate::{config::config_home, display::DisplayBytes};
+use std::{env, fmt, fs};
+
+/// A terminal emulator whose font can be read from its config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Terminal {
+    Kitty,
+    Alacritty,
+}
+
+impl fmt::Display for Terminal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Terminal::Kitty => "kitty",
+            Terminal::Alacritty => "Alacritty",
+        })
+    }
+}
+
+/// Recognizes the terminal from the variables it sets for its children.
+///
+/// `None` for any other terminal, and under multiplexers like tmux that hide the variables.
+pub fn terminal() -> Option<Terminal> {
+    if env::var_os("KITTY_WINDOW_ID").is_some() {
+        Some(Terminal::Kitty)
+    } else if env::var_os("ALACRITTY_WINDOW_ID").is_some() {
+        Some(Terminal::Alacritty)
+    } else {
+        None
+    }
+}
+
+/// The font family set in the config file of the current [`terminal`].
+///
+/// Best effort: `None` for unknown terminals, when the config file doesn't set a font,
+/// or when it's set in a file the config includes.
+pub fn terminal_font() -> Option<DisplayBytes<Vec<u8>>> {
+    let dir = config_home()?;
+    let font = match terminal()? {
+        Terminal::Kitty => kitty_font(&fs::read(dir.join("kitty/kitty.conf")).ok()?)?.to_vec(),
+        Terminal::Alacritty => {
+            alacritty_font(&fs::read(dir.join("alacritty/alacritty.toml")).ok()?)?.to_vec()
+        }
+    };
+    Some(DisplayBytes(font))
+}
+
+/// The last `font_family` line of a `kitty.conf`, e.g. `font_family JetBrains Mono`.
+///
+/// `auto`, which lets kitty pick a monospace font, yields `None`.
+fn kitty_font(config: &[u8]) -> Option<&[u8]> {
+    config
+        .split(|&b| b == b'\n')
+        .rev()
+        .find_map(|line| {
+            let line = line.trim_ascii();
+            let value = line.strip_prefix(b"font_family")?;
+            // Not a longer option such as `font_family_fallback`
+            value.first().filter(|b| b.is_ascii_whitespace())?;
+            Some(value.trim_ascii())
+        })
+        .filter(|font| !font.is_empty() && *font != b"auto")
+}
+
+/// `family` in the `[font.normal]` table of an `alacritty.toml`,
+/// or in a `normal = { family = "..." }` inline table under `[font]`.
+fn alacritty_font(config: &[u8]) -> Option<&[u8]> {
+    let mut table: &[u8] = b"";
+    for line in config.split(|&b| b == b'\n') {
+        let line = line.trim_ascii();
+        if let Some(header) = line.strip_prefix(b"[") {
+            table = header
+                .split(|&b| b == b']')
+                .next()
+                .unwrap_or_default()
+                .trim_ascii();
+            continue;
+        }
+        let value = match table {
+            b"font.normal" => toml_value(line, b"family"),
+            b"font" => toml_value(line, b"normal").and_then(|inline| {
+                let inline = inline.strip_prefix(b"{")?;
+                let family = inline.windows(6).position(|w| w == b"family")?;
+                toml_value(&inline[family..], b"family")
+            }),
+            _ => None,
+        };
+        if let Some(value) = value {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// The value of `key = value` at the start of `line`, without the quotes if it's a string.
+fn toml_value<'a>(line: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
+    let rest = line.strip_prefix(key)?.trim_ascii_start();
+    let value = rest.strip_prefix(b"=")?.trim_ascii();
+    match value {
+        [quote @ (b'"' | b'\''), rest @ ..] => {
+            let end = rest.iter().position(|b| b == quote)?;
+            Some(&rest[..end])
+        }
+        _ => Some(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kitty() {
+        let config = b"# font_family Commented\nfont_family      Fira Code\nfont_family_fallback x\nfont_size 11.0\nfont_family JetBrains Mono\n";
+        assert_eq!(kitty_font(config), Some(&b"JetBrains Mono"[..]));
+        assert_eq!(kitty_font(b"font_family Hack\nfont_family auto\n"), None);
+        assert_eq!(kitty_font(b"font_size 12\n"), None);
+    }
+
+    #[test]
+    fn alacritty() {
+        let config = b"[window]\nopacity = 0.9\n\n[font.normal]\nfamily = \"Iosevka Term\"\nstyle = \"Regular\"\n";
+        assert_eq!(alacritty_font(config), Some(&b"Iosevka Term"[..]));
+        let inline = b"[font]\nsize = 11\nnormal = { family = 'Hack', style = 'Regular' }\n";
+        assert_eq!(alacritty_font(inline), Some(&b"Hack"[..]));
+        // Another table's family
+        assert_eq!(alacritty_font(b"[font.bold]\nfamily = \"Hack\"\n"), None);
+    }
+}
@@ -0,0 +1,145 @@
+//! Per-component gather times, to find out which component slows the fetch down.
+//!
+//! Only compiled with the `timing` feature, so [`super::gather`] pays nothing for it otherwise.
+use super::{gather_with, Component, ComponentError, OutputOrder};
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// How long each component took to gather, created by [`gather_timed`].
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    /// Labels and gather times, in the order the values were passed on.
+    pub components: Vec<(String, Duration)>,
+    /// The time from starting the first component to passing on the last value.
+    pub wall: Duration,
+}
+
+impl Timings {
+    /// The sum of every component's gather time, which exceeds [`Timings::wall`]
+    /// as components are gathered concurrently.
+    pub fn total(&self) -> Duration {
+        self.components.iter().map(|(_, time)| time).sum()
+    }
+    /// The component that took the longest.
+    pub fn slowest(&self) -> Option<(&str, Duration)> {
+        self.components
+            .iter()
+            .max_by_key(|(_, time)| *time)
+            .map(|(label, time)| (label.as_str(), *time))
+    }
+}
+
+/// Like [`super::gather`], also measuring how long each component took.
+pub fn gather_timed<'c>(
+    components: &'c [Box<dyn Component>],
+    order: OutputOrder,
+    mut f: impl FnMut(&'c dyn Component, Result<String, ComponentError>),
+) -> Timings {
+    let start = Instant::now();
+    let mut timings = Timings::default();
+    gather_with(
+        components,
+        order,
+        |component| {
+            let start = Instant::now();
+            let value = component.value();
+            (value, start.elapsed())
+        },
+        |component, (value, time)| {
+            timings
+                .components
+                .push((component.label().to_string(), time));
+            f(component, value);
+        },
+    );
+    timings.wall = start.elapsed();
+    timings
+}
+
+/// A table of the components and their times, followed by the totals.
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .components
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .chain(["Total (sum)".len()])
+            .max()
+            .unwrap_or_default();
+        let row = |f: &mut fmt::Formatter<'_>, label: &str, time: Duration| {
+            // Duration's Debug output ignores width, so pad the formatted string instead
+            writeln!(f, "{label:<width$}  {:>10}", format!("{time:.2?}"))
+        };
+        for (label, time) in &self.components {
+            row(f, label, *time)?;
+        }
+        row(f, "Total (sum)", self.total())?;
+        row(f, "Wall clock", self.wall)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Barrier};
+
+    /// Only finishes once every other component sharing the barrier has started.
+    struct Together(&'static str, Arc<Barrier>);
+
+    impl Component for Together {
+        fn label(&self) -> &str {
+            self.0
+        }
+        fn value(&self) -> Result<String, ComponentError> {
+            self.1.wait();
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn gathered() {
+        // Would never finish if the components weren't gathered concurrently
+        let barrier = Arc::new(Barrier::new(2));
+        let components: Vec<Box<dyn Component>> = vec![
+            Box::new(Together("GPU", Arc::clone(&barrier))),
+            Box::new(Together("CPU", barrier)),
+        ];
+        let mut seen = 0;
+        let timings = gather_timed(&components, OutputOrder::Stable, |_, value| {
+            assert!(value.is_ok());
+            seen += 1;
+        });
+        assert_eq!(seen, 2);
+        let labels: Vec<_> = timings.components.iter().map(|(label, _)| label).collect();
+        assert_eq!(labels, ["GPU", "CPU"]);
+        assert_eq!(timings.to_string().lines().count(), 4);
+    }
+
+    #[test]
+    fn breakdown() {
+        let timings = Timings {
+            components: vec![
+                ("GPU".to_string(), Duration::from_millis(60)),
+                ("CPU".to_string(), Duration::from_millis(30)),
+            ],
+            wall: Duration::from_millis(61),
+        };
+        assert_eq!(timings.slowest(), Some(("GPU", Duration::from_millis(60))));
+        assert_eq!(timings.total(), Duration::from_millis(90));
+        assert_eq!(Timings::default().slowest(), None);
+
+        let table = timings.to_string();
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "GPU             60.00ms",
+                "CPU             30.00ms",
+                "Total (sum)     90.00ms",
+                "Wall clock      61.00ms",
+            ]
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! Time since boot.
+use crate::parse::{proc_stat_btime, proc_uptime};
+use std::{
+    fmt, fs, io,
+    mem::MaybeUninit,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use winnow::Parser;
+
+const PROC_UPTIME: &str = "/proc/uptime";
+const PROC_STAT: &str = "/proc/stat";
+
+/// When the system booted, from the `btime` line of `/proc/stat`.
+///
+/// `None` if `/proc/stat` can't be read or has no `btime` line.
+pub fn boot_time() -> Option<SystemTime> {
+    boot_time_from(&fs::read(PROC_STAT).ok()?)
+}
+
+fn boot_time_from(stat: &[u8]) -> Option<SystemTime> {
+    let btime = proc_stat_btime.parse_next(&mut &stat[..]).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(btime))
+}
+
+/// A wall-clock time in the local timezone, displayed as e.g. `2024-06-01 08:30`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LocalTime {
+    pub year: i32,
+    /// 1 to 12.
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl LocalTime {
+    /// Converts `time` using the timezone from `TZ` or `/etc/localtime`.
+    ///
+    /// `None` for times before the epoch or that the C library can't convert.
+    pub fn from_system(time: SystemTime) -> Option<Self> {
+        let secs = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let secs = libc::time_t::try_from(secs).ok()?;
+        let mut tm = MaybeUninit::<libc::tm>::uninit();
+        // SAFETY: both pointers are valid, localtime_r is the thread-safe variant
+        if unsafe { libc::localtime_r(&secs, tm.as_mut_ptr()) }.is_null() {
+            return None;
+        }
+        // SAFETY: localtime_r succeeded, so tm is initialized
+        let tm = unsafe { tm.assume_init() };
+        Some(Self {
+            year: tm.tm_year + 1900,
+            month: (tm.tm_mon + 1) as u8,
+            day: tm.tm_mday as u8,
+            hour: tm.tm_hour as u8,
+            minute: tm.tm_min as u8,
+        })
+    }
+}
+
+impl fmt::Display for LocalTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute
+        )
+    }
+}
+
+/// How an [`Uptime`] is written. Either way, leading units that are zero are left out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UptimeStyle {
+    /// `3d 4h 12m`, for status bars and `--oneline`.
+    #[default]
+    Compact,
+    /// `3 days, 4 hours, 12 minutes`
+    Verbose,
+}
+
+/// Time since boot, displayed as e.g. `3d 4h 12m`, see [`UptimeStyle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Uptime(pub Duration);
+
+impl Uptime {
+    pub fn get() -> io::Result<Self> {
+        Self::parse(&fs::read(PROC_UPTIME)?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed /proc/uptime"))
+    }
+    /// Parses the contents of `/proc/uptime`, returning `None` if the uptime isn't
+    /// a representable, non-negative number of seconds.
+    pub fn parse(buf: &[u8]) -> Option<Self> {
+        let uptime = proc_uptime.parse_next(&mut &buf[..]).ok()?;
+        Duration::try_from_secs_f64(uptime.uptime).ok().map(Self)
+    }
+    /// The uptime split into `(days, hours, minutes)`.
+    pub fn parts(&self) -> (u64, u64, u64) {
+        let minutes = self.0.as_secs() / 60;
+        (minutes / (24 * 60), minutes / 60 % 24, minutes % 60)
+    }
+    /// Displays the uptime written in `style`.
+    pub fn with_style(self, style: UptimeStyle) -> StyledUptime {
+        StyledUptime {
+            uptime: self,
+            style,
+        }
+    }
+}
+
+impl fmt::Display for Uptime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with_style(UptimeStyle::default()).fmt(f)
+    }
+}
+
+/// Created by [`Uptime::with_style`].
+#[derive(Debug, Clone, Copy)]
+pub struct StyledUptime {
+    uptime: Uptime,
+    style: UptimeStyle,
+}
+
+impl fmt::Display for StyledUptime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (days, hours, minutes) = self.uptime.parts();
+        let units = [
+            (days, "d", "day"),
+            (hours, "h", "hour"),
+            (minutes, "m", "minute"),
+        ];
+        // Minutes are always shown, even when zero
+        let first = units[..2].iter().take_while(|unit| unit.0 == 0).count();
+        for (i, &(value, short, long)) in units[first..].iter().enumerate() {
+            match self.style {
+                UptimeStyle::Compact => {
+                    let sep = if i == 0 { "" } else { " " };
+                    write!(f, "{sep}{value}{short}")?;
+                }
+                UptimeStyle::Verbose => {
+                    let sep = if i == 0 { "" } else { ", " };
+                    let plural = if value == 1 { "" } else { "s" };
+                    write!(f, "{sep}{value} {long}{plural}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Uptime::parse(b"350735.47 234388.90\n"),
+            Some(Uptime(Duration::from_millis(350735470)))
+        );
+        assert_eq!(Uptime::parse(b"-1.00 0.00\n"), None);
+        assert_eq!(Uptime::parse(b"1e300 0.00\n"), None);
+        assert_eq!(Uptime::parse(b"nan 0.00\n"), None);
+        assert_eq!(Uptime::parse(b"350735.47\n"), None);
+    }
+
+    #[test]
+    fn compact() {
+        let uptime = |secs| Uptime(Duration::from_secs(secs)).to_string();
+        assert_eq!(uptime(59), "0m");
+        assert_eq!(uptime(3 * 3600 + 60), "3h 1m");
+        assert_eq!(uptime(3 * 86400 + 4 * 3600 + 12 * 60), "3d 4h 12m");
+        assert_eq!(uptime(86400), "1d 0h 0m");
+    }
+
+    #[test]
+    fn verbose() {
+        let uptime = |secs| {
+            Uptime(Duration::from_secs(secs))
+                .with_style(UptimeStyle::Verbose)
+                .to_string()
+        };
+        assert_eq!(uptime(59), "0 minutes");
+        assert_eq!(uptime(60), "1 minute");
+        assert_eq!(uptime(3600 + 2 * 60), "1 hour, 2 minutes");
+        assert_eq!(
+            uptime(3 * 86400 + 4 * 3600 + 12 * 60),
+            "3 days, 4 hours, 12 minutes"
+        );
+        assert_eq!(uptime(86400 + 60), "1 day, 0 hours, 1 minute");
+    }
+
+    #[test]
+    fn boot() {
+        let stat = b"cpu  1 2 3 4\nctxt 115315133\nbtime 1717230600\nprocesses 86031\n";
+        assert_eq!(
+            boot_time_from(stat),
+            Some(UNIX_EPOCH + Duration::from_secs(1717230600))
+        );
+        assert_eq!(boot_time_from(b"ctxt 1\n"), None);
+
+        let time = LocalTime {
+            year: 2024,
+            month: 6,
+            day: 1,
+            hour: 8,
+            minute: 5,
+        };
+        assert_eq!(time.to_string(), "2024-06-01 08:05");
+        assert!(LocalTime::from_system(UNIX_EPOCH + Duration::from_secs(1717230600)).is_some());
+    }
+}
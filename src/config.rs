@@ -0,0 +1,216 @@
+//! User configuration, read from `~/.config/rxfetch/config.toml` with the `serde` feature.
+use crate::{
+    components::{
+        self,
+        name::{current_uid, PwuId},
+        Component,
+    },
+    display::ColorMode,
+};
+use std::{collections::BTreeMap, env, path::PathBuf};
+
+/// Persistent settings, overridden by command line flags.
+///
+/// ```toml
+/// components = ["user", "kernel", "memory", "gpu-clock"]
+/// format = "{label} -> {value}"
+/// color = "never"
+/// icons = true
+/// logo = "arch"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct Config {
+    /// Names of the components to show, as printed by `--list-components`,
+    /// all default components when empty.
+    pub components: Vec<String>,
+    /// How each line is shown, see [`crate::fetch::Fetch::format`].
+    pub format: Option<String>,
+    pub color: ColorMode,
+    pub icons: Icons,
+    /// The name of the built-in logo shown next to the information, see [`crate::render::logo`].
+    pub logo: Option<String>,
+}
+
+/// Whether Nerd Font glyphs are shown instead of labels, see [`crate::render::icon`].
+///
+/// Either `icons = true`, or a table of glyphs by label that replace or add to the built-in
+/// ones, which enables them too:
+///
+/// ```toml
+/// [icons]
+/// Kernel = "\u{f31a}"
+/// Weather = "\u{e30d}"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(from = "IconsRepr")
+)]
+pub struct Icons {
+    pub enabled: bool,
+    /// Glyphs by component label, matched ignoring case.
+    pub glyphs: BTreeMap<String, String>,
+}
+
+/// The two ways [`Icons`] can be written.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum IconsRepr {
+    Enabled(bool),
+    Glyphs(BTreeMap<String, String>),
+}
+
+#[cfg(feature = "serde")]
+impl From<IconsRepr> for Icons {
+    fn from(repr: IconsRepr) -> Self {
+        match repr {
+            IconsRepr::Enabled(enabled) => Self {
+                enabled,
+                glyphs: BTreeMap::new(),
+            },
+            IconsRepr::Glyphs(glyphs) => Self {
+                enabled: true,
+                glyphs,
+            },
+        }
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/rxfetch/config.toml`, or `~/.config/rxfetch/config.toml`
+    /// when `XDG_CONFIG_HOME` isn't set.
+    pub fn path() -> Option<PathBuf> {
+        let mut path = config_home()?;
+        path.push("rxfetch/config.toml");
+        Some(path)
+    }
+    /// Reads the config file, a missing file yields the defaults.
+    #[cfg(feature = "serde")]
+    pub fn load() -> Result<Self, ConfigError> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::from_toml(&contents),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(ConfigError::IOError(err)),
+        }
+    }
+    #[cfg(feature = "serde")]
+    pub fn from_toml(contents: &str) -> Result<Self, ConfigError> {
+        toml::from_str(contents).map_err(ConfigError::Parse)
+    }
+    /// The components named in [`Config::components`] in that order, or the
+    /// [`components::default_components`] when none are named. Unknown names are skipped.
+    pub fn selected_components(&self) -> Vec<Box<dyn Component>> {
+        if self.components.is_empty() {
+            return components::default_components();
+        }
+        self.components
+            .iter()
+            .filter_map(|name| components::by_name(name))
+            .collect()
+    }
+    /// The names in [`Config::components`] that no component has.
+    pub fn unknown_components(&self) -> impl Iterator<Item = &str> {
+        self.components
+            .iter()
+            .map(String::as_str)
+            .filter(|name| components::by_name(name).is_none())
+    }
+}
+
+/// `$XDG_CONFIG_HOME`, or `~/.config` when it isn't set.
+pub(crate) fn config_home() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => Some(
+            PwuId::get_alloc(current_uid())
+                .ok()?
+                .home_path()
+                .join(".config"),
+        ),
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum ConfigError {
+    IOError(std::io::Error),
+    Parse(toml::de::Error),
+}
+
+#[cfg(feature = "serde")]
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::IOError(err) => write!(f, "IO error: {err}"),
+            ConfigError::Parse(err) => write!(f, "invalid config: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::IOError(err) => Some(err),
+            ConfigError::Parse(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let config = Config::from_toml(
+            r#"
+            components = ["user", "Memory"]
+            color = "never"
+            logo = "arch"
+            format = "{label} -> {value}"
+            icons = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.color, ColorMode::Never);
+        assert_eq!(config.logo.as_deref(), Some("arch"));
+        assert_eq!(config.format.as_deref(), Some("{label} -> {value}"));
+        assert!(config.icons.enabled);
+        assert!(config.icons.glyphs.is_empty());
+        let labels: Vec<_> = config
+            .selected_components()
+            .iter()
+            .map(|component| component.label().to_string())
+            .collect();
+        assert_eq!(labels, ["User", "Memory"]);
+        assert_eq!(Config::from_toml("").unwrap(), Config::default());
+        assert!(Config::from_toml("colour = \"never\"").is_err());
+    }
+
+    #[test]
+    fn icon_table() {
+        let config = Config::from_toml(
+            r#"
+            [icons]
+            Kernel = "K"
+            "Local IP" = "IP"
+            "#,
+        )
+        .unwrap();
+        assert!(config.icons.enabled);
+        assert_eq!(config.icons.glyphs["Local IP"], "IP");
+        assert!(!Config::from_toml("icons = false").unwrap().icons.enabled);
+        assert!(Config::from_toml("icons = 1").is_err());
+    }
+}
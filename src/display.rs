@@ -0,0 +1,489 @@
+//! Helpers for rendering raw bytes read from the system.
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Write},
+};
+
+/// Character emitted in place of bytes that can't be rendered.
+pub const REPLACEMENT: char = char::REPLACEMENT_CHARACTER;
+
+/// Displays a byte buffer as UTF-8, replacing invalid sequences with [`REPLACEMENT`].
+///
+/// Output matches [`String::from_utf8_lossy`] without allocating.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DisplayBytes<B: AsRef<[u8]>>(pub B);
+
+impl<B: AsRef<[u8]>> DisplayBytes<B> {
+    pub const fn new(bytes: B) -> Self {
+        Self(bytes)
+    }
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.as_bytes().is_empty()
+    }
+    pub fn into_inner(self) -> B {
+        self.0
+    }
+    /// Writes the bytes as the body of a JSON string, without the surrounding quotes.
+    ///
+    /// Invalid UTF-8 is replaced as in [`Display`], then `"`, `\` and control characters
+    /// are escaped.
+    pub fn write_json_escaped(&self, f: &mut impl Write) -> fmt::Result {
+        for chunk in self.as_bytes().utf8_chunks() {
+            let valid = chunk.valid();
+            // Runs of characters that need no escaping are written at once
+            let mut start = 0;
+            for (idx, c) in valid.char_indices() {
+                if !matches!(c, '"' | '\\' | '\0'..='\x1f') {
+                    continue;
+                }
+                f.write_str(&valid[start..idx])?;
+                start = idx + 1;
+                match c {
+                    '"' => f.write_str("\\\"")?,
+                    '\\' => f.write_str("\\\\")?,
+                    '\n' => f.write_str("\\n")?,
+                    '\r' => f.write_str("\\r")?,
+                    '\t' => f.write_str("\\t")?,
+                    _ => write!(f, "\\u{:04x}", c as u32)?,
+                }
+            }
+            f.write_str(&valid[start..])?;
+            if !chunk.invalid().is_empty() {
+                f.write_char(REPLACEMENT)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<B: AsRef<[u8]>> Display for DisplayBytes<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.as_bytes().utf8_chunks() {
+            f.write_str(chunk.valid())?;
+            if !chunk.invalid().is_empty() {
+                f.write_char(REPLACEMENT)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<B: AsRef<[u8]>> fmt::Debug for DisplayBytes<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+/// Displays UTF-16 text such as Windows computer and user names,
+/// replacing unpaired surrogates with [`REPLACEMENT`].
+///
+/// Output matches [`String::from_utf16_lossy`] without allocating.
+/// Not tied to Windows, so it's available on every platform.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DisplayWide<B: AsRef<[u16]>>(pub B);
+
+impl<B: AsRef<[u16]>> DisplayWide<B> {
+    pub fn as_wide(&self) -> &[u16] {
+        self.0.as_ref()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.as_wide().is_empty()
+    }
+    pub fn into_inner(self) -> B {
+        self.0
+    }
+}
+
+impl<B: AsRef<[u16]>> Display for DisplayWide<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in char::decode_utf16(self.as_wide().iter().copied()) {
+            f.write_char(c.unwrap_or(REPLACEMENT))?;
+        }
+        Ok(())
+    }
+}
+
+impl<B: AsRef<[u16]>> fmt::Debug for DisplayWide<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+/// Renders bytes as `xxd`-style rows: offset, hex columns and an ASCII gutter.
+///
+/// Non-printable bytes are shown as [`REPLACEMENT`] in the gutter.
+#[derive(Clone, Copy)]
+pub struct HexDump<'b>(pub &'b [u8]);
+
+impl HexDump<'_> {
+    const ROW: usize = 16;
+}
+
+impl Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (row, bytes) in self.0.chunks(Self::ROW).enumerate() {
+            if row != 0 {
+                f.write_char('\n')?;
+            }
+            write!(f, "{:08x}:", row * Self::ROW)?;
+            for col in 0..Self::ROW {
+                if col % 2 == 0 {
+                    f.write_char(' ')?;
+                }
+                match bytes.get(col) {
+                    Some(b) => write!(f, "{b:02x}")?,
+                    None => f.write_str("  ")?,
+                }
+            }
+            f.write_str("  ")?;
+            for &b in bytes {
+                if b.is_ascii_graphic() || b == b' ' {
+                    f.write_char(b as char)?;
+                } else {
+                    f.write_char(REPLACEMENT)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether [`Styled`] values emit their SGR escape codes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum ColorMode {
+    /// Color when stdout is a terminal and `NO_COLOR` isn't set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Parses `auto`, `always` or `never`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(ColorMode::Auto),
+            "always" => Some(ColorMode::Always),
+            "never" => Some(ColorMode::Never),
+            _ => None,
+        }
+    }
+    /// Resolves [`ColorMode::Auto`] against the environment.
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+                // SAFETY: isatty has no preconditions
+                !no_color && unsafe { libc::isatty(libc::STDOUT_FILENO) } == 1
+            }
+        }
+    }
+}
+
+/// A single line of text paired with an optional SGR code, e.g. `1;34` for bold blue.
+///
+/// The code and a trailing reset are only emitted when color is enabled,
+/// so the text itself never has to carry escape sequences.
+#[derive(Debug, Clone, Copy)]
+pub struct Styled<T> {
+    value: T,
+    sgr: Option<&'static str>,
+    color: bool,
+}
+
+impl<T: Display> Styled<T> {
+    pub fn new(value: T, sgr: Option<&'static str>, mode: ColorMode) -> Self {
+        Self {
+            value,
+            sgr,
+            color: mode.enabled(),
+        }
+    }
+}
+
+impl<T: Display> Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.sgr {
+            Some(sgr) if self.color => write!(f, "\x1b[{sgr}m{}\x1b[0m", self.value),
+            _ => self.value.fmt(f),
+        }
+    }
+}
+
+/// The number of characters `s` takes up on screen, not counting escape sequences.
+pub fn visible_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            match chars.next() {
+                // CSI sequences end at the first byte in 0x40..=0x7e after the `[`
+                Some('[') => {
+                    chars.find(|c| ('\x40'..='\x7e').contains(c));
+                }
+                // OSC sequences, e.g. hyperlinks, end at BEL or ST (`ESC \`)
+                Some(']') => {
+                    let mut prev = '\0';
+                    chars.find(|&c| {
+                        let end = c == '\x07' || (prev == '\x1b' && c == '\\');
+                        prev = c;
+                        end
+                    });
+                }
+                _ => {}
+            }
+            continue;
+        }
+        width += 1;
+    }
+    width
+}
+
+/// Removes escape sequences from captured output, such as the colors in a `--version` banner,
+/// so they can't leak into the layout when rendered through [`DisplayBytes`].
+///
+/// CSI sequences like `\x1b[1;32m` are removed up to their final byte, OSC sequences like
+/// hyperlinks up to their BEL or ST terminator, and other escapes along with the byte after
+/// the `ESC`, matching [`visible_width`]. A sequence cut off by the end of the buffer is dropped.
+/// Borrows when there's nothing to strip.
+pub fn strip_ansi(bytes: &[u8]) -> Cow<'_, [u8]> {
+    if !bytes.contains(&0x1b) {
+        return Cow::Borrowed(bytes);
+    }
+    let mut stripped = Vec::with_capacity(bytes.len());
+    let mut bytes = bytes.iter().copied();
+    while let Some(b) = bytes.next() {
+        if b != 0x1b {
+            stripped.push(b);
+            continue;
+        }
+        match bytes.next() {
+            Some(b'[') => {
+                bytes.find(|b| (0x40..=0x7e).contains(b));
+            }
+            Some(b']') => {
+                let mut prev = 0;
+                bytes.find(|&b| {
+                    let end = b == 0x07 || (prev == 0x1b && b == b'\\');
+                    prev = b;
+                    end
+                });
+            }
+            _ => {}
+        }
+    }
+    Cow::Owned(stripped)
+}
+
+/// Width adjustments for anything that implements [`Display`].
+pub trait DisplayExt: Display {
+    /// Cuts the output to at most `cols` characters, ending it with `…` when anything was cut.
+    fn truncated(&self, cols: usize) -> FixedWidth<&Self> {
+        FixedWidth {
+            value: self,
+            cols,
+            pad: false,
+        }
+    }
+    /// Like [`DisplayExt::truncated`], but pads shorter output with spaces on the right,
+    /// so the result is always exactly `cols` characters wide.
+    fn fixed_width(&self, cols: usize) -> FixedWidth<&Self> {
+        FixedWidth {
+            value: self,
+            cols,
+            pad: true,
+        }
+    }
+}
+
+impl<T: Display + ?Sized> DisplayExt for T {}
+
+/// Created by [`DisplayExt::truncated`] and [`DisplayExt::fixed_width`].
+#[derive(Debug, Clone, Copy)]
+pub struct FixedWidth<T> {
+    value: T,
+    cols: usize,
+    pad: bool,
+}
+
+impl<T: Display> Display for FixedWidth<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut writer = TruncatingWriter {
+            out: f,
+            remaining: self.cols,
+            pending: None,
+            cut: false,
+        };
+        // The writer reports a cut as an error to stop formatting early
+        if write!(writer, "{}", self.value).is_err() && !writer.cut {
+            return Err(fmt::Error);
+        }
+        let TruncatingWriter {
+            out,
+            remaining,
+            pending,
+            cut,
+        } = writer;
+        match (pending, cut) {
+            (Some(_), true) => out.write_char('…')?,
+            (Some(c), false) => out.write_char(c)?,
+            (None, _) => {}
+        }
+        if self.pad {
+            for _ in 0..remaining {
+                out.write_char(' ')?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes characters until `remaining` runs out, holding the last one back
+/// until it's known whether it has to be replaced by `…`.
+struct TruncatingWriter<'a, 'f> {
+    out: &'a mut fmt::Formatter<'f>,
+    remaining: usize,
+    pending: Option<char>,
+    cut: bool,
+}
+
+impl Write for TruncatingWriter<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.pending.is_some() {
+                self.cut = true;
+                return Err(fmt::Error);
+            }
+            match self.remaining {
+                0 => {
+                    self.cut = true;
+                    return Err(fmt::Error);
+                }
+                1 => self.pending = Some(c),
+                _ => self.out.write_char(c)?,
+            }
+            self.remaining -= 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_escaped() {
+        let json = |bytes: &[u8]| {
+            let mut out = String::new();
+            DisplayBytes(bytes).write_json_escaped(&mut out).unwrap();
+            out
+        };
+        assert_eq!(json(b"me@box"), "me@box");
+        assert_eq!(json(b"say \"hi\""), r#"say \"hi\""#);
+        assert_eq!(json(b"C:\\Users\\"), r#"C:\\Users\\"#);
+        assert_eq!(json(b"a\tb\r\n"), r#"a\tb\r\n"#);
+        assert_eq!(json(b"\x00\x1b[0m\x7f"), "\\u0000\\u001b[0m\x7f");
+        assert_eq!(json(b"caf\xc3\xa9 \xff\"x"), "café \u{fffd}\\\"x");
+        assert_eq!(json(b""), "");
+    }
+
+    #[test]
+    fn bytes_match_lossy() {
+        let cases: [&[u8]; 8] = [
+            b"plain ascii",
+            "Æ, 🦀".as_bytes(),
+            b"\xffabc",
+            b"abc\xe2\x82",
+            b"ab\x80cd\xc3\xa6",
+            // Invalid bytes right after a valid prefix, one replacement each
+            b"\xc3\xa6\xff\xfe!",
+            // An overlong encoding and a surrogate, both rejected byte by byte
+            b"\xc0\xaf\xed\xa0\x80x",
+            b"",
+        ];
+        for bytes in cases {
+            assert_eq!(
+                DisplayBytes(bytes).to_string(),
+                String::from_utf8_lossy(bytes),
+                "{bytes:x?}"
+            );
+        }
+    }
+
+    #[test]
+    fn wide() {
+        let name: Vec<u16> = "DESKTOP-ÆØ🦀".encode_utf16().collect();
+        assert_eq!(DisplayWide(&name).to_string(), "DESKTOP-ÆØ🦀");
+        // A lone high surrogate, then a lone low surrogate at the end
+        let broken = [0x0041, 0xd83e, 0x0042, 0xdd80];
+        assert_eq!(
+            DisplayWide(broken).to_string(),
+            String::from_utf16_lossy(&broken)
+        );
+        assert_eq!(DisplayWide(broken).to_string(), "A\u{fffd}B\u{fffd}");
+    }
+
+    #[test]
+    fn strip_escapes() {
+        let banner = b"\x1b[1;32mstarship\x1b[0m 1.20.1\n\x1b7done";
+        assert_eq!(&*strip_ansi(banner), b"starship 1.20.1\ndone");
+        assert!(matches!(strip_ansi(b"plain"), Cow::Borrowed(b"plain")));
+        // Cut off mid-sequence
+        assert_eq!(&*strip_ansi(b"text\x1b[38;5"), b"text");
+        assert_eq!(&*strip_ansi(b"text\x1b"), b"text");
+        // OSC hyperlinks end at ST, window titles usually at BEL
+        let link = b"\x1b]8;;https://starship.rs\x1b\\starship\x1b]8;;\x1b\\ 1.20.1";
+        assert_eq!(&*strip_ansi(link), b"starship 1.20.1");
+        assert_eq!(&*strip_ansi(b"\x1b]0;title\x07text"), b"text");
+        assert_eq!(&*strip_ansi(b"text\x1b]0;tit"), b"text");
+    }
+
+    #[test]
+    fn hexdump_row() {
+        let bytes = b"Hello, world!\n\x00\xff";
+        assert_eq!(
+            HexDump(bytes).to_string(),
+            "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a 00ff  Hello, world!\u{fffd}\u{fffd}\u{fffd}"
+        );
+    }
+
+    #[test]
+    fn styled_width() {
+        let label = Styled::new(DisplayBytes(b"CPU"), Some("1;34"), ColorMode::Always).to_string();
+        assert_eq!(label, "\x1b[1;34mCPU\x1b[0m");
+        assert_eq!(visible_width(&label), 3);
+        assert_eq!(
+            Styled::new("CPU", Some("1;34"), ColorMode::Never).to_string(),
+            "CPU"
+        );
+        assert_eq!(visible_width("\x1b[38;5;208mrx\x1b[0mfetch"), 7);
+        assert_eq!(visible_width("°C"), 2);
+        assert_eq!(
+            visible_width("\x1b]8;;https://rxfetch\x1b\\rx\x1b]8;;\x07"),
+            2
+        );
+    }
+
+    #[test]
+    fn fixed_width() {
+        let host = DisplayBytes(b"workstation");
+        assert_eq!(host.fixed_width(14).to_string(), "workstation   ");
+        assert_eq!(host.fixed_width(11).to_string(), "workstation");
+        assert_eq!(host.fixed_width(8).to_string(), "worksta…");
+        assert_eq!(host.truncated(14).to_string(), "workstation");
+        assert_eq!("°C°C".truncated(3).to_string(), "°C…");
+        assert_eq!("abc".fixed_width(0).to_string(), "");
+        assert_eq!("".fixed_width(2).to_string(), "  ");
+    }
+}
@@ -0,0 +1,574 @@
+//! Assembling the whole fetch from a library, without going through the binary.
+//!
+//! ```no_run
+//! use rxfetch::{display::ColorMode, fetch::Fetch};
+//!
+//! let output = Fetch::new()
+//!     .cpu()
+//!     .memory()
+//!     .uptime()
+//!     .color(ColorMode::Never)
+//!     .render();
+//! println!("{output}");
+//! ```
+use crate::{
+    components::{self, gather, Component, ComponentError, OutputOrder},
+    config::Config,
+    display::{visible_width, ColorMode, DisplayExt, Styled},
+    render::{self, icon, palette},
+};
+use std::{
+    fmt,
+    io::{self, Write},
+    time::Instant,
+};
+
+/// Longer values are cut to this many characters by [`Fetch::render_oneline`].
+const ONELINE_FIELD_WIDTH: usize = 40;
+
+/// A builder for the fetch output, see the [module docs](self).
+///
+/// Components are shown in the order they were enabled, and are gathered concurrently by [`gather`].
+#[derive(Default)]
+pub struct Fetch {
+    components: Vec<Box<dyn Component>>,
+    color: ColorMode,
+    logo: Option<String>,
+    format: Option<String>,
+    palette: bool,
+    icons: bool,
+    /// Glyphs set with [`Fetch::icon`], by label.
+    custom_icons: Vec<(String, String)>,
+    order: OutputOrder,
+    debug: bool,
+    report_errors: bool,
+    #[cfg(feature = "timing")]
+    timings: bool,
+}
+
+impl Fetch {
+    /// A fetch without any components.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// A fetch with the components `config` selects, see [`Config::selected_components`],
+    /// in its color mode, format and logo. An unknown logo is left out.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            components: config.selected_components(),
+            color: config.color,
+            icons: config.icons.enabled,
+            custom_icons: config
+                .icons
+                .glyphs
+                .iter()
+                .map(|(label, glyph)| (label.clone(), glyph.clone()))
+                .collect(),
+            logo: config
+                .logo
+                .as_deref()
+                .and_then(render::logo)
+                .map(String::from),
+            format: config.format.clone(),
+            ..Self::default()
+        }
+    }
+    /// The curated set of components for a single line, e.g. in a shell prompt:
+    /// `user@host`, the kernel, the CPU and the GPU. See [`Fetch::render_oneline`].
+    pub fn oneline() -> Self {
+        Self::new().user().kernel().cpu().gpu()
+    }
+    /// Adds any component, including ones defined outside this crate.
+    pub fn with(mut self, component: impl Component + 'static) -> Self {
+        self.components.push(Box::new(component));
+        self
+    }
+    /// Adds an already boxed component, e.g. one picked at runtime.
+    pub fn custom(mut self, component: Box<dyn Component>) -> Self {
+        self.components.push(component);
+        self
+    }
+    pub fn user(self) -> Self {
+        self.with(components::User)
+    }
+    pub fn kernel(self) -> Self {
+        self.with(components::Kernel)
+    }
+    pub fn uptime(self) -> Self {
+        self.with(components::Uptime)
+    }
+    pub fn boot_time(self) -> Self {
+        self.with(components::BootTime)
+    }
+    pub fn init(self) -> Self {
+        self.with(components::Init)
+    }
+    pub fn session(self) -> Self {
+        self.with(components::Session)
+    }
+    pub fn display_manager(self) -> Self {
+        self.with(components::DisplayManager)
+    }
+    pub fn cpu(self) -> Self {
+        self.with(components::Cpu)
+    }
+    pub fn gpu(self) -> Self {
+        self.with(components::Gpu)
+    }
+    pub fn gpu_clock(self) -> Self {
+        self.with(components::GpuClock)
+    }
+    pub fn memory(self) -> Self {
+        self.with(components::Memory)
+    }
+    pub fn disk(self) -> Self {
+        self.with(components::Disk)
+    }
+    pub fn modules(self) -> Self {
+        self.with(components::Modules)
+    }
+    pub fn local_ip(self) -> Self {
+        self.with(components::LocalIp)
+    }
+    pub fn entropy(self) -> Self {
+        self.with(components::Entropy)
+    }
+    pub fn cache(self) -> Self {
+        self.with(components::Cache)
+    }
+    pub fn virtualization(self) -> Self {
+        self.with(components::Virtualization)
+    }
+    pub fn terminal_font(self) -> Self {
+        self.with(components::TerminalFont)
+    }
+    pub fn processes(self) -> Self {
+        self.with(components::Processes)
+    }
+    pub fn sound(self) -> Self {
+        self.with(components::Sound)
+    }
+    pub fn firmware(self) -> Self {
+        self.with(components::Firmware)
+    }
+    pub fn secure_boot(self) -> Self {
+        self.with(components::SecureBoot)
+    }
+    pub fn tunables(self) -> Self {
+        self.with(components::Tunables)
+    }
+    /// Whether labels are styled, [`ColorMode::Auto`] by default.
+    pub fn color(mut self, mode: ColorMode) -> Self {
+        self.color = mode;
+        self
+    }
+    /// Shows `art` to the left of the information, one line of art per line of output.
+    ///
+    /// The art may contain escape sequences, they're ignored when aligning the information.
+    pub fn logo(mut self, art: impl Into<String>) -> Self {
+        self.logo = Some(art.into());
+        self
+    }
+    /// Shows each line as `format`, with `{label}` and `{value}` replaced,
+    /// instead of `{label}: {value}`.
+    pub fn format(mut self, format: impl Into<String>) -> Self {
+        self.format = Some(format.into());
+        self
+    }
+    /// A line of output, in the configured format.
+    fn line(&self, label: &str, value: &str) -> String {
+        let label = Styled::new(self.label(label), Some("1"), self.color).to_string();
+        let Some(format) = &self.format else {
+            return format!("{label}: {value}");
+        };
+        // Splitting first keeps placeholders inside the label or value from being replaced
+        format
+            .split("{value}")
+            .map(|part| part.replace("{label}", &label))
+            .collect::<Vec<_>>()
+            .join(value)
+    }
+    /// Shows Nerd Font glyphs instead of component labels, see [`icon`].
+    ///
+    /// Off by default, since the glyphs only render with a patched font.
+    /// Components without a glyph keep their label.
+    pub fn icons(mut self, enabled: bool) -> Self {
+        self.icons = enabled;
+        self
+    }
+    /// Shows `glyph` for the component with `label` when icons are enabled,
+    /// replacing the built-in one or giving one to a custom component.
+    pub fn icon(mut self, label: impl Into<String>, glyph: impl Into<String>) -> Self {
+        self.custom_icons.push((label.into(), glyph.into()));
+        self
+    }
+    /// What's shown before the value of the component with `label`.
+    fn label<'a>(&'a self, label: &'a str) -> &'a str {
+        if !self.icons {
+            return label;
+        }
+        let custom = self
+            .custom_icons
+            .iter()
+            .rev()
+            .find(|(name, _)| name.eq_ignore_ascii_case(label))
+            .map(|(_, glyph)| glyph.as_str());
+        custom.or_else(|| icon(label)).unwrap_or(label)
+    }
+    /// Appends the [`palette`] swatches, which are only shown when color is enabled.
+    pub fn palette(mut self) -> Self {
+        self.palette = true;
+        self
+    }
+    /// The order the lines are written in, [`OutputOrder::Stable`] by default.
+    ///
+    /// With a logo every line is held back until all are ready, to align them next to it.
+    pub fn order(mut self, order: OutputOrder) -> Self {
+        self.order = order;
+        self
+    }
+    /// Writes when each component became ready to stderr, as `[debug] CPU ready after 1.2ms`.
+    pub fn debug(mut self, enabled: bool) -> Self {
+        self.debug = enabled;
+        self
+    }
+    /// Writes components that fail to stderr, as `Failed to read CPU: ...`,
+    /// instead of leaving them out silently.
+    pub fn report_errors(mut self, enabled: bool) -> Self {
+        self.report_errors = enabled;
+        self
+    }
+    /// Writes how long each component took to stderr once all are gathered,
+    /// see [`components::timing::Timings`].
+    #[cfg(feature = "timing")]
+    pub fn timings(mut self, enabled: bool) -> Self {
+        self.timings = enabled;
+        self
+    }
+    /// Gathers every component in the configured order, passing on each line of output.
+    fn gather_lines(&self, mut line: impl FnMut(String)) {
+        let start = Instant::now();
+        let mut pci_reported = false;
+        let f = |component: &dyn Component, value: Result<String, ComponentError>| {
+            if self.debug {
+                eprintln!(
+                    "[debug] {} ready after {:?}",
+                    component.label(),
+                    start.elapsed()
+                );
+            }
+            match value {
+                Ok(value) => line(self.line(component.label(), &value)),
+                Err(ComponentError::Unavailable) => {}
+                // Every PCI component fails the same way, one line covers them all
+                Err(ComponentError::PciUnavailable) => {
+                    if !std::mem::replace(&mut pci_reported, true) {
+                        line(self.line("PCI", "unavailable"));
+                    }
+                }
+                Err(err) if self.report_errors => {
+                    eprintln!("Failed to read {}: {err}", component.label())
+                }
+                Err(_) => {}
+            }
+        };
+        #[cfg(feature = "timing")]
+        if self.timings {
+            eprint!(
+                "{}",
+                components::timing::gather_timed(&self.components, self.order, f)
+            );
+            return;
+        }
+        gather(&self.components, self.order, f);
+    }
+    /// Gathers every component and writes the output to `out`.
+    ///
+    /// Without a logo each line is written as soon as the [`Fetch::order`] allows.
+    /// Components that aren't available on this system are left out, as are ones that fail
+    /// unless [`Fetch::report_errors`] is set. When PCI devices can't be enumerated at all,
+    /// a single `PCI: unavailable` line takes the place of the components that need them.
+    pub fn write_to(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        let logo: Vec<&str> = self
+            .logo
+            .as_deref()
+            .map_or(Vec::new(), |art| art.lines().collect());
+        let mut lines = Vec::with_capacity(self.components.len());
+        let mut result = Ok(());
+        let mut emit = |line: String| {
+            if !logo.is_empty() {
+                lines.push(line);
+            } else if result.is_ok() {
+                result = writeln!(out, "{line}");
+            }
+        };
+        self.gather_lines(&mut emit);
+        if self.palette && self.color.enabled() {
+            emit(String::new());
+            palette(self.color)
+                .to_string()
+                .lines()
+                .for_each(|line| emit(line.to_string()));
+        }
+        result?;
+
+        let logo_width = logo.iter().map(|line| visible_width(line)).max();
+        for idx in 0..lines.len().max(logo.len()) {
+            let art = logo.get(idx).copied().unwrap_or_default();
+            out.write_str(art)?;
+            if let Some(line) = lines.get(idx) {
+                if let Some(width) = logo_width {
+                    // Two spaces between the art and the information
+                    write!(out, "{:1$}", "", width - visible_width(art) + 2)?;
+                }
+                out.write_str(line)?;
+            }
+            out.write_char('\n')?;
+        }
+        Ok(())
+    }
+    /// Gathers every component and joins their values on one line with `separator`,
+    /// without labels. Each value is cut to 40 characters, and empty ones are left out.
+    ///
+    /// The logo, palette and color mode don't apply.
+    pub fn render_oneline(&self, separator: &str) -> String {
+        let mut fields = Vec::with_capacity(self.components.len());
+        gather(
+            &self.components,
+            OutputOrder::Stable,
+            |_, value| match value {
+                Ok(value) if !value.is_empty() => {
+                    fields.push(value.truncated(ONELINE_FIELD_WIDTH).to_string())
+                }
+                _ => {}
+            },
+        );
+        fields.join(separator)
+    }
+    /// Gathers every component and returns the output, see [`Fetch::write_to`].
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        // Writing to a String can't fail
+        let _ = self.write_to(&mut out);
+        out
+    }
+    /// Gathers every component and prints the output to stdout, see [`Fetch::write_to`].
+    pub fn print(&self) -> io::Result<()> {
+        let mut out = IoWriter {
+            inner: io::stdout().lock(),
+            error: Ok(()),
+        };
+        if self.write_to(&mut out).is_err() {
+            out.error?;
+        }
+        out.inner.flush()
+    }
+}
+
+/// Writes formatted output to an [`io::Write`], keeping the error that [`fmt::Error`] can't carry.
+struct IoWriter<W> {
+    inner: W,
+    error: io::Result<()>,
+}
+
+impl<W: Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Err(err);
+            fmt::Error
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str, Option<&'static str>);
+
+    impl Component for Fixed {
+        fn label(&self) -> &str {
+            self.0
+        }
+        fn value(&self) -> Result<String, ComponentError> {
+            self.1.map(String::from).ok_or(ComponentError::Unavailable)
+        }
+    }
+
+    #[test]
+    fn render() {
+        let fetch = Fetch::new()
+            .with(Fixed("OS", Some("Linux")))
+            .with(Fixed("Missing", None))
+            .with(Fixed("Shell", Some("fish")))
+            .color(ColorMode::Never);
+        assert_eq!(fetch.render(), "OS: Linux\nShell: fish\n");
+
+        let fetch = fetch.logo("\x1b[34m/\\\x1b[0m\n/  \\\n----");
+        assert_eq!(
+            fetch.render(),
+            "\x1b[34m/\\\x1b[0m    OS: Linux\n/  \\  Shell: fish\n----\n"
+        );
+    }
+
+    #[test]
+    fn formatted() {
+        let fetch = Fetch::new()
+            .with(Fixed("OS", Some("{label}")))
+            .with(NoPci("GPU"))
+            .color(ColorMode::Never)
+            .format("[{label}] {value} ({label})");
+        assert_eq!(
+            fetch.render(),
+            "[OS] {label} (OS)\n[PCI] unavailable (PCI)\n"
+        );
+        let config = Config {
+            format: Some("{value}".to_string()),
+            logo: Some("Linux".to_string()),
+            ..Config::default()
+        };
+        let fetch = Fetch::from_config(&config);
+        assert_eq!(fetch.format.as_deref(), Some("{value}"));
+        assert_eq!(fetch.logo.as_deref(), render::logo("linux"));
+    }
+
+    #[test]
+    fn icons() {
+        let fetch = Fetch::new()
+            .with(Fixed("Kernel", Some("6.9.1")))
+            .with(Fixed("Weather", Some("sunny")))
+            .with(Fixed("Shell", Some("fish")))
+            .color(ColorMode::Never)
+            .logo("/\\\n\\/");
+        assert_eq!(
+            fetch.render(),
+            "/\\  Kernel: 6.9.1\n\\/  Weather: sunny\n    Shell: fish\n"
+        );
+        let fetch = fetch.icons(true).icon("weather", "\u{e30d}");
+        assert_eq!(
+            fetch.render(),
+            "/\\  \u{f17c}: 6.9.1\n\\/  \u{e30d}: sunny\n    Shell: fish\n"
+        );
+
+        // Overrides from the config replace the built-in glyphs
+        let mut config = Config::default();
+        config.icons.enabled = true;
+        config.icons.glyphs.insert("kernel".into(), "K".into());
+        let fetch = Fetch::from_config(&config);
+        assert_eq!(fetch.label("Kernel"), "K");
+        assert_eq!(fetch.label("Memory"), icon("Memory").unwrap());
+    }
+
+    /// A component needing PCI devices where they can't be enumerated.
+    struct NoPci(&'static str);
+
+    impl Component for NoPci {
+        fn label(&self) -> &str {
+            self.0
+        }
+        fn value(&self) -> Result<String, ComponentError> {
+            Err(ComponentError::PciUnavailable)
+        }
+    }
+
+    #[test]
+    fn pci_unavailable() {
+        let fetch = Fetch::new()
+            .with(Fixed("OS", Some("Linux")))
+            .with(NoPci("GPU"))
+            .with(NoPci("GPU Clock"))
+            .with(Fixed("Shell", Some("fish")))
+            .color(ColorMode::Never);
+        assert_eq!(fetch.render(), "OS: Linux\nPCI: unavailable\nShell: fish\n");
+        let fetch = fetch.color(ColorMode::Always);
+        assert!(fetch
+            .render()
+            .contains("\n\x1b[1mPCI\x1b[0m: unavailable\n"));
+        assert_eq!(fetch.render_oneline(" | "), "Linux | fish");
+    }
+
+    #[test]
+    fn configured_components() {
+        let labels = |fetch: Fetch| -> Vec<String> {
+            fetch
+                .components
+                .iter()
+                .map(|component| component.label().to_string())
+                .collect()
+        };
+        // Every name --list-components prints can be configured, including non-default ones
+        for entry in components::REGISTRY.iter().filter(|entry| !entry.default) {
+            let config = Config {
+                components: vec![entry.name.to_string(), "nonexistent".to_string()],
+                ..Config::default()
+            };
+            assert_eq!(
+                labels(Fetch::from_config(&config)),
+                [(entry.new)().label()],
+                "{}",
+                entry.name
+            );
+            assert_eq!(
+                config.unknown_components().collect::<Vec<_>>(),
+                ["nonexistent"]
+            );
+        }
+        assert_eq!(
+            labels(Fetch::from_config(&Config::default())).len(),
+            components::default_components().len()
+        );
+    }
+
+    #[test]
+    fn oneline() {
+        let long = "AMD Ryzen 9 7950X3D 16-Core Processor (32) @ 5.76 GHz";
+        let fetch = Fetch::new()
+            .with(Fixed("User", Some("me@box")))
+            .with(Fixed("Missing", None))
+            .with(Fixed("Empty", Some("")))
+            .with(Fixed("CPU", Some(long)))
+            .logo("/\\");
+        assert_eq!(
+            fetch.render_oneline(" | "),
+            "me@box | AMD Ryzen 9 7950X3D 16-Core Processor (…"
+        );
+        assert_eq!(Fetch::new().render_oneline(" | "), "");
+    }
+
+    /// A component defined outside the crate, as a downstream user would write it.
+    struct Weather {
+        city: String,
+    }
+
+    impl Component for Weather {
+        fn label(&self) -> &str {
+            "Weather"
+        }
+        fn value(&self) -> Result<String, ComponentError> {
+            Ok(format!("{}: 21°C, sunny", self.city))
+        }
+    }
+
+    #[test]
+    fn custom_component() {
+        let weather: Box<dyn Component> = Box::new(Weather {
+            city: "Oslo".to_string(),
+        });
+        let fetch = Fetch::new()
+            .with(Fixed("OS", Some("Linux")))
+            .custom(weather)
+            .color(ColorMode::Never);
+        assert_eq!(fetch.render(), "OS: Linux\nWeather: Oslo: 21°C, sunny\n");
+    }
+
+    #[test]
+    fn styled_labels() {
+        let fetch = Fetch::new()
+            .with(Fixed("OS", Some("Linux")))
+            .color(ColorMode::Always)
+            .palette();
+        let output = fetch.render();
+        assert!(output.starts_with("\x1b[1mOS\x1b[0m: Linux\n\n"));
+        assert_eq!(output.lines().count(), 4);
+    }
+}
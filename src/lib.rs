@@ -0,0 +1,14 @@
+pub mod arrayvec;
+pub mod bytesize;
+pub mod components;
+pub mod config;
+pub mod display;
+pub mod fetch;
+pub mod parse;
+pub mod path;
+pub mod pci;
+pub mod prelude;
+pub mod render;
+pub mod small_str;
+#[cfg(test)]
+mod test_util;
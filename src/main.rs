@@ -1 +1,130 @@
-fn main() {}
+use rxfetch::{
+    components::{OutputOrder, REGISTRY},
+    config::Config,
+    display::{ColorMode, Styled},
+    fetch::Fetch,
+    pci::{gpu, ids::PciIdDb, PciAutoIter, PciBackendError, PciDevIterBackend},
+    render::logo,
+};
+use std::time::Instant;
+
+#[cfg(feature = "serde")]
+fn load_config() -> Config {
+    Config::load().unwrap_or_else(|err| {
+        eprintln!("Failed to load config, using defaults: {err}");
+        Config::default()
+    })
+}
+
+#[cfg(not(feature = "serde"))]
+fn load_config() -> Config {
+    Config::default()
+}
+
+/// The line the fetch shows in place of the PCI components, see [`Fetch::write_to`].
+fn print_pci_unavailable(color: ColorMode) {
+    println!("{}: unavailable", Styled::new("PCI", Some("1"), color));
+}
+
+fn main() {
+    let start = Instant::now();
+    let mut config = load_config();
+    let mut list_pci = false;
+    let mut list_gpus = false;
+    let mut oneline = false;
+    let mut order = OutputOrder::Stable;
+    // Timing diagnostics go to stderr, and only when asked for
+    let mut debug = std::env::var_os("RUST_LOG").is_some_and(|level| !level.is_empty());
+    for arg in std::env::args().skip(1) {
+        if arg == "--pci" {
+            list_pci = true;
+        } else if arg == "--gpus" {
+            list_gpus = true;
+        } else if arg == "--oneline" {
+            oneline = true;
+        } else if arg == "--list-components" {
+            for entry in REGISTRY {
+                let default = if entry.default {
+                    ""
+                } else {
+                    " (not shown by default)"
+                };
+                println!("{}{default}", entry.name);
+            }
+            return;
+        } else if arg == "--debug" {
+            debug = true;
+        } else if arg == "--as-ready" {
+            order = OutputOrder::AsReady;
+        } else if let Some(mode) = arg.strip_prefix("--color=") {
+            match ColorMode::from_name(mode) {
+                Some(mode) => config.color = mode,
+                None => eprintln!("Unknown color mode {mode:?}, expected auto, always or never"),
+            }
+        }
+    }
+
+    if list_pci {
+        let devices = match PciAutoIter::try_init() {
+            Ok(devices) => devices,
+            Err(PciBackendError::NotAvailable) => {
+                print_pci_unavailable(config.color);
+                return;
+            }
+            Err(err) => {
+                eprintln!("Failed to enumerate PCI devices: {err}");
+                return;
+            }
+        };
+        let db = PciIdDb::system();
+        for device in devices.resolved() {
+            match device {
+                Ok(device) => println!("{}", db.display(&device)),
+                Err(err) => eprintln!("Failed to read PCI device: {err}"),
+            }
+        }
+        if debug {
+            eprintln!("[debug] listing PCI devices took {:?}", start.elapsed());
+        }
+        return;
+    }
+    if list_gpus {
+        match gpu::list_gpus() {
+            Ok(gpus) => {
+                for gpu in gpus {
+                    println!("{} {gpu}", gpu.address());
+                    for connector in gpu.connectors() {
+                        println!("    {connector}");
+                    }
+                }
+            }
+            Err(PciBackendError::NotAvailable) => print_pci_unavailable(config.color),
+            Err(err) => eprintln!("Failed to enumerate GPUs: {err}"),
+        }
+        return;
+    }
+    if oneline {
+        let separator = std::env::var("RXFETCH_SEP").unwrap_or_else(|_| " | ".to_string());
+        println!("{}", Fetch::oneline().render_oneline(&separator));
+        return;
+    }
+    for name in config.unknown_components() {
+        eprintln!("Unknown component {name:?}, see --list-components");
+    }
+    if let Some(logo) = config.logo.as_deref().filter(|name| logo(name).is_none()) {
+        eprintln!("Unknown logo {logo:?}");
+    }
+    let fetch = Fetch::from_config(&config)
+        .order(order)
+        .debug(debug)
+        .report_errors(true)
+        .palette();
+    #[cfg(feature = "timing")]
+    let fetch = fetch.timings(true);
+    if let Err(err) = fetch.print() {
+        eprintln!("Failed to write the fetch: {err}");
+    }
+    if debug {
+        eprintln!("[debug] fetch took {:?}", start.elapsed());
+    }
+}
@@ -0,0 +1,175 @@
+//! Parsers for the text formats found in `/proc` and `/sys`.
+use crate::display::DisplayBytes;
+use winnow::{
+    ascii::{dec_int, dec_uint, float, line_ending, space1, till_line_ending, Int, Uint},
+    combinator::{delimited, opt, preceded, repeat_till, terminated},
+    error::{ContextError, ErrMode},
+    prelude::*,
+    token::take,
+};
+
+/// Parses an unsigned decimal integer.
+pub fn decimal<T: Uint>(input: &mut &[u8]) -> ModalResult<T> {
+    dec_uint(input)
+}
+
+/// The value of an ASCII hex digit.
+pub const fn unhex(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// A text attribute such as `scaling_governor` or `/proc/1/comm`, with the surrounding
+/// whitespace removed in place, so e.g. the newline of `amdgpu\n` doesn't end up in the output.
+///
+/// Numeric attributes don't need it, the `sysfs_*` parsers accept the trailing newline.
+pub fn trim_text(mut text: Vec<u8>) -> DisplayBytes<Vec<u8>> {
+    text.truncate(text.trim_ascii_end().len());
+    let leading = text.len() - text.trim_ascii_start().len();
+    text.drain(..leading);
+    DisplayBytes(text)
+}
+
+/// Parses exactly `digits` hex digits, at most 8.
+pub fn fixed_hex<'i>(digits: usize) -> impl Parser<&'i [u8], u32, ErrMode<ContextError>> {
+    debug_assert!(digits <= 8);
+    take(digits).verify_map(|hex: &[u8]| {
+        hex.iter()
+            .try_fold(0u32, |acc, &digit| Some(acc << 4 | unhex(digit)? as u32))
+    })
+}
+
+/// Splits `key<sep>value` lines, as found in `/proc/cpuinfo` and `/proc/meminfo`,
+/// trimming whitespace around both halves. Lines without `sep` are skipped.
+pub fn key_values(buf: &[u8], sep: u8) -> impl Iterator<Item = (&[u8], &[u8])> {
+    buf.split(|&b| b == b'\n').filter_map(move |line| {
+        let idx = line.iter().position(|&b| b == sep)?;
+        Some((line[..idx].trim_ascii(), line[idx + 1..].trim_ascii()))
+    })
+}
+
+/// Parses a sysfs hex attribute of exactly `digits` digits, such as `0x10de\n`.
+pub fn sysfs_hex(attr: &[u8], digits: usize) -> Option<u32> {
+    delimited(b"0x", fixed_hex(digits), opt(b'\n'))
+        .parse(attr)
+        .ok()
+}
+
+/// Parses a sysfs decimal attribute, such as `4400000\n`.
+pub fn sysfs_decimal<T: Uint>(attr: &[u8]) -> Option<T> {
+    terminated(decimal, opt(b'\n')).parse(attr).ok()
+}
+
+/// Parses a signed sysfs decimal attribute, such as `-1\n`.
+pub fn sysfs_signed<T: Int>(attr: &[u8]) -> Option<T> {
+    terminated(dec_int::<_, _, ContextError>, opt(b'\n'))
+        .parse(attr)
+        .ok()
+}
+
+/// The contents of `/proc/uptime`, in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcUptime {
+    /// Time since boot, including time spent suspended.
+    pub uptime: f64,
+    /// Sum of the time every core spent idle, which may exceed `uptime` on multi-core systems.
+    pub idle: f64,
+}
+
+/// Parses `/proc/uptime`, e.g. `350735.47 234388.90`.
+pub fn proc_uptime(input: &mut &[u8]) -> ModalResult<ProcUptime> {
+    (terminated(float, space1), float)
+        .map(|(uptime, idle)| ProcUptime { uptime, idle })
+        .parse_next(input)
+}
+
+/// Finds the `btime <epoch>` line in `/proc/stat`, returning the boot time in seconds since the Unix epoch.
+pub fn proc_stat_btime(input: &mut &[u8]) -> ModalResult<u64> {
+    repeat_till(
+        0..,
+        (till_line_ending, line_ending),
+        preceded((b"btime", space1), decimal),
+    )
+    .map(|((), btime)| btime)
+    .parse_next(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex() {
+        assert_eq!(
+            fixed_hex(4).parse_peek(&b"10de\n"[..]).unwrap(),
+            (&b"\n"[..], 0x10de)
+        );
+        assert!(fixed_hex(4).parse_peek(&b"10d"[..]).is_err());
+        assert!(fixed_hex(2).parse_peek(&b"0x"[..]).is_err());
+        // pci.ids and user input may use uppercase digits
+        assert_eq!(fixed_hex(4).parse(&b"10DE"[..]).unwrap(), 0x10de);
+        assert_eq!(fixed_hex(4).parse(&b"AbCf"[..]).unwrap(), 0xabcf);
+        assert_eq!(sysfs_hex(b"0x1AF4\n", 4), Some(0x1af4));
+        assert!(fixed_hex(2).parse(&b"0G"[..]).is_err());
+        assert_eq!(unhex(b'F'), Some(15));
+        assert_eq!(unhex(b'g'), None);
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(trim_text(b"amdgpu\n".to_vec()).as_bytes(), b"amdgpu");
+        assert_eq!(
+            trim_text(b" Radeon Pro\t\r\n".to_vec()).as_bytes(),
+            b"Radeon Pro"
+        );
+        assert!(trim_text(b"\n".to_vec()).is_empty());
+        assert!(trim_text(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn key_value_lines() {
+        let meminfo = b"MemTotal:       32768000 kB\nMemFree:  1024 kB\n\nnot a field\n";
+        assert_eq!(
+            key_values(meminfo, b':').collect::<Vec<_>>(),
+            [
+                (&b"MemTotal"[..], &b"32768000 kB"[..]),
+                (&b"MemFree"[..], &b"1024 kB"[..])
+            ]
+        );
+    }
+
+    #[test]
+    fn uptime() {
+        let parsed = proc_uptime
+            .parse_peek(&b"350735.47 234388.90\n"[..])
+            .unwrap()
+            .1;
+        assert_eq!(
+            parsed,
+            ProcUptime {
+                uptime: 350735.47,
+                idle: 234388.90
+            }
+        );
+        assert!(proc_uptime.parse_peek(&b"350735.47\n"[..]).is_err());
+    }
+
+    #[test]
+    fn btime() {
+        let stat = b"cpu  10132153 290696 3084719 46828483 16683 0 25195 0 0 0\n\
+            cpu0 1393280 32966 572056 13343292 6130 0 17875 0 0 0\n\
+            intr 1462898 0 0 0 0 0 0 0 0 0\n\
+            ctxt 115315133\n\
+            btime 1718015400\n\
+            processes 86031\n\
+            procs_running 6\n";
+        assert_eq!(proc_stat_btime.parse_peek(&stat[..]).unwrap().1, 1718015400);
+        assert!(proc_stat_btime
+            .parse_peek(&b"ctxt 1\nprocesses 2\n"[..])
+            .is_err());
+    }
+}
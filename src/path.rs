@@ -0,0 +1,89 @@
+//! Temporary path manipulation without reallocating.
+use std::{
+    ffi::OsString,
+    mem,
+    ops::Deref,
+    os::unix::ffi::OsStringExt,
+    path::{Path, PathBuf},
+};
+
+/// Pushes a relative path onto a [`PathBuf`] for the lifetime of the guard,
+/// restoring the original path on drop.
+///
+/// The original path is restored by truncating back to its previous length rather than popping
+/// components, so segments with several components, a trailing slash or `.` are all undone exactly.
+pub struct WrapPath<'p> {
+    path: &'p mut PathBuf,
+    len: usize,
+}
+
+impl<'p> WrapPath<'p> {
+    /// # Panics
+    /// Panics if `segment` is absolute, since pushing it would replace `path` entirely.
+    pub fn new(path: &'p mut PathBuf, segment: impl AsRef<Path>) -> Self {
+        let segment = segment.as_ref();
+        assert!(
+            segment.is_relative(),
+            "WrapPath segment {segment:?} must be relative"
+        );
+        let len = path.as_os_str().len();
+        path.push(segment);
+        Self { path, len }
+    }
+    /// Pushes another segment, which is removed when the returned guard is dropped.
+    pub fn push(&mut self, segment: impl AsRef<Path>) -> WrapPath<'_> {
+        WrapPath::new(self.path, segment)
+    }
+}
+
+impl Deref for WrapPath<'_> {
+    type Target = Path;
+    fn deref(&self) -> &Path {
+        self.path
+    }
+}
+
+impl AsRef<Path> for WrapPath<'_> {
+    fn as_ref(&self) -> &Path {
+        self.path
+    }
+}
+
+impl Drop for WrapPath<'_> {
+    fn drop(&mut self) {
+        let mut bytes = mem::take(self.path).into_os_string().into_vec();
+        bytes.truncate(self.len);
+        *self.path = OsString::from_vec(bytes).into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_segment_push() {
+        let mut path = PathBuf::from("/sys/bus/pci/devices/0000:03:00.0");
+        {
+            let wrapped = WrapPath::new(&mut path, "driver/module");
+            assert_eq!(
+                &*wrapped,
+                Path::new("/sys/bus/pci/devices/0000:03:00.0/driver/module")
+            );
+        }
+        assert_eq!(path, Path::new("/sys/bus/pci/devices/0000:03:00.0"));
+    }
+
+    #[test]
+    fn trailing_slash_and_cur_dir() {
+        let mut path = PathBuf::from("/sys/class/drm");
+        for segment in ["card0/", "./card0", "card0/./device/", "."] {
+            {
+                let mut wrapped = WrapPath::new(&mut path, segment);
+                let nested = wrapped.push("hwmon");
+                assert!(nested.ends_with("hwmon"));
+            }
+            assert_eq!(path, Path::new("/sys/class/drm"), "segment {segment:?}");
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Parsing of the raw PCI config space header, independent of how it was read.
+//!
+//! Offsets used, all little-endian:
+//! - `0x00` vendor ID, `0x02` device ID
+//! - `0x04` command register, see [`CommandFlags`]
+//! - `0x09` programming interface, `0x0a` subclass, `0x0b` class
+//! - `0x0e` header type, bit 7 marks multi-function devices
+//! - `0x2c` subsystem vendor ID, `0x2e` subsystem ID, type 0 headers only
+use super::{PciBackendError, PciClass};
+use std::fmt;
+
+/// The identifying fields of a config space header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub vendor: u16,
+    pub device: u16,
+    pub class: PciClass,
+    /// `(vendor, device)`, only present for non-bridge devices.
+    pub subsystem: Option<(u16, u16)>,
+}
+
+/// Parses the identifying fields from a raw config space buffer, e.g. the contents of a
+/// `/proc/bus/pci` device file.
+///
+/// Never panics, buffers too short to hold a field yield [`PciBackendError::InvalidDevice`].
+pub fn parse_config_space(buf: &[u8]) -> Result<DeviceInfo, PciBackendError> {
+    Ok(DeviceInfo {
+        vendor: read_u16(buf, 0x00)?,
+        device: read_u16(buf, 0x02)?,
+        class: read_class(buf)?,
+        subsystem: match (read_subsystem(buf, 0x2c), read_subsystem(buf, 0x2e)) {
+            (Ok(vendor), Ok(device)) => Some((vendor, device)),
+            (Err(PciBackendError::NotAvailable), _) => None,
+            (Err(err), _) | (_, Err(err)) => return Err(err),
+        },
+    })
+}
+
+/// The command register at offset `0x04`, which controls how the device may access the bus.
+///
+/// A device that's present but unused, e.g. a GPU without a bound driver, typically has
+/// memory space decoding or bus mastering disabled.
+///
+/// Displays like `lspci -vv`'s `Control:` line, e.g. `I/O- Mem+ BusMaster+ ... DisINTx+`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CommandFlags(pub u16);
+
+impl CommandFlags {
+    /// Bit 0, responds to I/O space accesses.
+    pub const IO_SPACE: Self = Self(1 << 0);
+    /// Bit 1, responds to memory space accesses, e.g. a GPU's BARs.
+    pub const MEMORY_SPACE: Self = Self(1 << 1);
+    /// Bit 2, may initiate DMA.
+    pub const BUS_MASTER: Self = Self(1 << 2);
+    /// Bit 3, monitors special cycles.
+    pub const SPECIAL_CYCLES: Self = Self(1 << 3);
+    /// Bit 4, may use Memory Write and Invalidate.
+    pub const MEMORY_WRITE_INVALIDATE: Self = Self(1 << 4);
+    /// Bit 5, snoops VGA palette writes.
+    pub const VGA_PALETTE_SNOOP: Self = Self(1 << 5);
+    /// Bit 6, reports parity errors.
+    pub const PARITY_ERROR_RESPONSE: Self = Self(1 << 6);
+    /// Bit 8, reports system errors through `SERR#`.
+    pub const SERR: Self = Self(1 << 8);
+    /// Bit 9, may do fast back-to-back transactions.
+    pub const FAST_BACK_TO_BACK: Self = Self(1 << 9);
+    /// Bit 10, legacy `INTx` interrupts are disabled, usually because MSI is used instead.
+    pub const INTERRUPT_DISABLE: Self = Self(1 << 10);
+
+    /// Whether every bit set in `flags` is set in `self`.
+    pub const fn contains(self, flags: Self) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+}
+
+impl fmt::Display for CommandFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const NAMES: [(CommandFlags, &str); 10] = [
+            (CommandFlags::IO_SPACE, "I/O"),
+            (CommandFlags::MEMORY_SPACE, "Mem"),
+            (CommandFlags::BUS_MASTER, "BusMaster"),
+            (CommandFlags::SPECIAL_CYCLES, "SpecCycle"),
+            (CommandFlags::MEMORY_WRITE_INVALIDATE, "MemWINV"),
+            (CommandFlags::VGA_PALETTE_SNOOP, "VGASnoop"),
+            (CommandFlags::PARITY_ERROR_RESPONSE, "ParErr"),
+            (CommandFlags::SERR, "SERR"),
+            (CommandFlags::FAST_BACK_TO_BACK, "FastB2B"),
+            (CommandFlags::INTERRUPT_DISABLE, "DisINTx"),
+        ];
+        for (idx, (flag, name)) in NAMES.into_iter().enumerate() {
+            if idx != 0 {
+                f.write_str(" ")?;
+            }
+            let state = if self.contains(flag) { '+' } else { '-' };
+            write!(f, "{name}{state}")?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn read_u16(buf: &[u8], offset: usize) -> Result<u16, PciBackendError> {
+    match buf.get(offset..offset + 2) {
+        Some(&[lo, hi]) => Ok(u16::from_le_bytes([lo, hi])),
+        _ => Err(PciBackendError::InvalidDevice),
+    }
+}
+
+pub(crate) fn read_class(buf: &[u8]) -> Result<PciClass, PciBackendError> {
+    match buf.get(0x09..0x0c) {
+        Some(&[prog_if, subclass, class]) => Ok(PciClass {
+            class,
+            subclass,
+            prog_if,
+        }),
+        _ => Err(PciBackendError::InvalidDevice),
+    }
+}
+
+/// Subsystem IDs are only present in the type 0 (non-bridge) header layout.
+pub(crate) fn read_subsystem(buf: &[u8], offset: usize) -> Result<u16, PciBackendError> {
+    match buf.get(0x0e) {
+        Some(header_type) if header_type & 0x7f == 0 => read_u16(buf, offset),
+        Some(_) => Err(PciBackendError::NotAvailable),
+        None => Err(PciBackendError::InvalidDevice),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Virtio network device, from a QEMU guest.
+    const VIRTIO_NET: [u8; 64] = [
+        0xf4, 0x1a, 0x41, 0x10, 0x06, 0x04, 0x10, 0x00, 0x01, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00,
+        0x00, 0x04, 0x00, 0x18, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf4,
+        0x1a, 0x41, 0x10, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+    ];
+    /// Multi-function NVIDIA GPU with an ASUS subsystem.
+    const NVIDIA_GPU: [u8; 64] = [
+        0xde, 0x10, 0x84, 0x26, 0x07, 0x04, 0x10, 0x00, 0xa1, 0x00, 0x00, 0x03, 0x00, 0x00, 0x80,
+        0x00, 0x00, 0x00, 0x00, 0xfb, 0x0c, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x0c, 0x00,
+        0x00, 0x00, 0x0a, 0x00, 0x00, 0x00, 0x01, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x43,
+        0x10, 0x9c, 0x88, 0x00, 0x00, 0x00, 0xfc, 0x60, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xff, 0x01, 0x00, 0x00,
+    ];
+    /// PCI-to-PCI bridge, whose type 1 header has no subsystem IDs.
+    const BRIDGE: [u8; 64] = [
+        0x22, 0x10, 0x83, 0x14, 0x07, 0x04, 0x10, 0x00, 0x00, 0x00, 0x04, 0x06, 0x00, 0x00, 0x81,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x00, 0xf1, 0x01,
+        0x00, 0x00, 0x00, 0xfc, 0x00, 0xfd, 0x01, 0xf0, 0xf1, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x50, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xff, 0x01, 0x12, 0x00,
+    ];
+
+    #[test]
+    fn seed_corpus() {
+        assert_eq!(
+            parse_config_space(&VIRTIO_NET).unwrap(),
+            DeviceInfo {
+                vendor: 0x1af4,
+                device: 0x1041,
+                class: PciClass {
+                    class: 0x02,
+                    subclass: 0x00,
+                    prog_if: 0x00
+                },
+                subsystem: Some((0x1af4, 0x1041)),
+            }
+        );
+        let gpu = parse_config_space(&NVIDIA_GPU).unwrap();
+        assert_eq!((gpu.vendor, gpu.device), (0x10de, 0x2684));
+        assert_eq!(gpu.class.class, PciClass::DISPLAY);
+        assert_eq!(gpu.subsystem, Some((0x1043, 0x889c)));
+        let bridge = parse_config_space(&BRIDGE).unwrap();
+        assert_eq!((bridge.class.class, bridge.class.subclass), (0x06, 0x04));
+        assert_eq!(bridge.subsystem, None);
+    }
+
+    #[test]
+    fn command() {
+        let command = CommandFlags(read_u16(&NVIDIA_GPU, 0x04).unwrap());
+        assert_eq!(command, CommandFlags(0x0407));
+        assert!(command.contains(CommandFlags::MEMORY_SPACE));
+        assert!(command.contains(CommandFlags::BUS_MASTER));
+        assert!(!command.contains(CommandFlags::SERR));
+        assert_eq!(
+            command.to_string(),
+            "I/O+ Mem+ BusMaster+ SpecCycle- MemWINV- VGASnoop- ParErr- SERR- FastB2B- DisINTx+"
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        for len in 0..0x30 {
+            assert!(
+                parse_config_space(&NVIDIA_GPU[..len]).is_err(),
+                "length {len}"
+            );
+        }
+        assert!(parse_config_space(&BRIDGE[..0x0f]).is_ok());
+    }
+}
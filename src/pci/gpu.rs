@@ -0,0 +1,314 @@
+//! Everything a fetch wants to know about the GPUs, in one call.
+use super::{
+    gpus_fast, ids::PciIdDb, primary_of, DeviceLabel, PciAddress, PciBackendError, PciDevice,
+    PciInfoProvider, PrettyName, ResolvedDevice,
+};
+use crate::bytesize::ByteSize;
+use std::{cmp::Reverse, fmt};
+
+/// A GPU with its driver and memory, created by [`list_gpus`].
+///
+/// Displays as e.g. `AMD Radeon RX 7900 XTX (24.0G) [amdgpu]`, using [`PrettyName`] with names from [`PciIdDb::system`].
+#[derive(Debug, Clone)]
+pub struct GpuSummary {
+    pub device: ResolvedDevice,
+    /// The firmware's name for the device, shown instead of the database name when present.
+    pub label: Option<DeviceLabel>,
+    /// The bound kernel driver, `None` without one.
+    pub driver: Option<String>,
+    /// `None` when the driver doesn't expose the amount of video memory.
+    pub vram_bytes: Option<u64>,
+    /// See [`PciAddress::is_first_root_bus`].
+    pub is_integrated: bool,
+}
+
+impl GpuSummary {
+    pub fn address(&self) -> PciAddress {
+        self.device.address
+    }
+    /// Looked up in [`PciIdDb::system`].
+    pub fn vendor_name(&self) -> Option<&'static str> {
+        PciIdDb::system().vendor_name(self.device.vendor_id)
+    }
+    /// Looked up in [`PciIdDb::system`].
+    pub fn device_name(&self) -> Option<&'static str> {
+        PciIdDb::system().device_name(self.device.vendor_id, self.device.device_id)
+    }
+    /// The connectors with a monitor attached, read from sysfs when called.
+    ///
+    /// Empty for GPUs without any, whose driver doesn't expose them, or off Linux.
+    pub fn connectors(&self) -> Vec<ConnectorInfo> {
+        #[cfg(target_os = "linux")]
+        return self.connectors_in(super::linux_sysfs::root());
+        #[cfg(not(target_os = "linux"))]
+        Vec::new()
+    }
+    /// Like [`Self::connectors`], looking under `root` instead of `/sys/bus/pci/devices`.
+    #[cfg(target_os = "linux")]
+    pub fn connectors_in(&self, root: impl Into<std::path::PathBuf>) -> Vec<ConnectorInfo> {
+        match super::linux_sysfs::SysBusBackend::device_at_in(root, self.address()) {
+            Ok(mut gpu) => gpu.connectors().unwrap_or_default(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+impl GpuSummary {
+    /// Displays the summary like [`fmt::Display`] does, with the name looked up in `db`.
+    pub fn display_with<'a>(&'a self, db: &'a PciIdDb) -> impl fmt::Display + 'a {
+        SummaryWithDb { summary: self, db }
+    }
+}
+
+impl fmt::Display for GpuSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display_with(PciIdDb::system()).fmt(f)
+    }
+}
+
+struct SummaryWithDb<'a> {
+    summary: &'a GpuSummary,
+    db: &'a PciIdDb,
+}
+
+impl fmt::Display for SummaryWithDb<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let summary = self.summary;
+        let name = PrettyName {
+            device: &summary.device,
+            label: summary.label.as_ref().map(DeviceLabel::as_bytes),
+            db: self.db,
+        };
+        write!(f, "{name}")?;
+        if let Some(vram) = summary.vram_bytes {
+            write!(f, " ({})", ByteSize(vram))?;
+        }
+        if let Some(driver) = &summary.driver {
+            write!(f, " [{driver}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// A display connector with a monitor attached, see [`PciDevice::connectors`].
+///
+/// Displays as e.g. `DP-1 3840x2160`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectorInfo {
+    /// The connector name without the card prefix, e.g. `DP-1` or `HDMI-A-1`.
+    pub name: String,
+    /// The monitor's preferred `(width, height)`, `None` if it reports no modes.
+    pub preferred_mode: Option<(u32, u32)>,
+}
+
+impl fmt::Display for ConnectorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some((width, height)) = self.preferred_mode {
+            write!(f, " {width}x{height}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Every GPU in the system, the primary one first and the rest ordered by address.
+///
+/// The GPUs are found through [`gpus_fast`] and the primary one is picked like
+/// [`super::PciAutoIter::primary_gpu`] does. GPUs that fail to read are skipped,
+/// while fields that can't be read are left empty.
+pub fn list_gpus() -> Result<Vec<GpuSummary>, PciBackendError> {
+    Ok(summarize(gpus_fast()?))
+}
+
+fn summarize<P: PciInfoProvider>(mut gpus: Vec<PciDevice<P>>) -> Vec<GpuSummary> {
+    gpus.sort_unstable_by_key(PciDevice::address);
+    gpus.dedup_by_key(|gpu| gpu.address());
+    let primary = primary_of(gpus.iter_mut()).map(|gpu| gpu.address());
+    if let Some(idx) =
+        primary.and_then(|primary| gpus.iter().position(|gpu| gpu.address() == primary))
+    {
+        gpus[..=idx].rotate_right(1);
+    }
+    gpus.into_iter().filter_map(summary).collect()
+}
+
+/// Reads everything in [`GpuSummary`], `None` if the device can't be resolved.
+pub(crate) fn summary<P: PciInfoProvider>(mut gpu: PciDevice<P>) -> Option<GpuSummary> {
+    let device = gpu.resolved().ok()?;
+    Some(GpuSummary {
+        label: gpu.label().ok().flatten(),
+        driver: gpu.driver(),
+        vram_bytes: gpu.vram_bytes(),
+        is_integrated: device.address.is_first_root_bus(),
+        device,
+    })
+}
+
+/// Sorts GPUs by VRAM, largest first, for [`super::PciAutoIter::gpus_by_vram`].
+///
+/// GPUs whose VRAM is unknown come last.
+/// Ties put discrete GPUs first, then the boot display, then go by address.
+pub(crate) fn by_vram<P: PciInfoProvider>(
+    gpus: impl IntoIterator<Item = PciDevice<P>>,
+) -> Vec<PciDevice<P>> {
+    let mut keyed: Vec<_> = gpus
+        .into_iter()
+        .map(|mut gpu| {
+            let address = gpu.address();
+            // Reversing the Option puts None after every Some
+            (
+                (
+                    Reverse(gpu.vram_bytes()),
+                    address.is_first_root_bus(),
+                    !gpu.is_boot_vga().unwrap_or(false),
+                    address,
+                ),
+                gpu,
+            )
+        })
+        .collect();
+    keyed.sort_unstable_by_key(|(key, _)| *key);
+    keyed.into_iter().map(|(_, gpu)| gpu).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci::{mock::MockProvider, PrettyDevice};
+
+    #[test]
+    fn summaries() {
+        let igpu = MockProvider::new(0x8086, 0xa780, 0x03, 0x00);
+        let mut dgpu = MockProvider::new(0x1002, 0x744c, 0x03, 0x00);
+        dgpu.driver = Some("amdgpu");
+        dgpu.vram = Some(24 << 30);
+        let gpus = vec![
+            PciDevice::from_address(0, 3, 0, 0).with_provider(dgpu),
+            PciDevice::from_address(0, 0, 2, 0).with_provider(igpu),
+            PciDevice::from_address(0, 3, 0, 0).with_provider(dgpu),
+        ];
+
+        let summaries = summarize(gpus);
+        assert_eq!(summaries.len(), 2);
+        let [primary, integrated] = &summaries[..] else {
+            unreachable!()
+        };
+        assert_eq!(primary.address().slot(), (0, 3, 0));
+        assert!(!primary.is_integrated);
+        assert_eq!(
+            primary.display_with(&PciIdDb::default()).to_string(),
+            format!(
+                "{} (24.0G) [amdgpu]",
+                PrettyDevice(primary.device.device.unwrap())
+            )
+        );
+        assert!(integrated.is_integrated);
+        assert_eq!(integrated.vram_bytes, None);
+        assert_eq!(integrated.vendor_name(), Some("Intel Corporation"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn connectors_in() {
+        use std::fs;
+        let root = crate::test_util::TempDir::new("gpu-connectors");
+        let card = root.join("0000:03:00.0/drm/card1");
+        for (connector, status) in [
+            ("card1-DP-1", "connected\n"),
+            ("card1-HDMI-A-1", "disconnected\n"),
+        ] {
+            fs::create_dir_all(card.join(connector)).unwrap();
+            fs::write(card.join(connector).join("status"), status).unwrap();
+            fs::write(card.join(connector).join("modes"), "3840x2160\n").unwrap();
+        }
+        let gpu = summarize(vec![PciDevice::from_address(0, 3, 0, 0)
+            .with_provider(MockProvider::new(0x1002, 0x744c, 0x03, 0x00))])
+        .remove(0);
+        let shown: Vec<_> = gpu
+            .connectors_in(&root)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(shown, ["DP-1 3840x2160"]);
+
+        // No device directory for the GPU
+        let mut absent = gpu.clone();
+        absent.device.address.bus = 4;
+        assert!(absent.connectors_in(&root).is_empty());
+    }
+
+    #[test]
+    fn sorted_by_vram() {
+        let mut small = MockProvider::new(0x1002, 0x73ff, 0x03, 0x00);
+        small.vram = Some(8 << 30);
+        let mut large = MockProvider::new(0x1002, 0x744c, 0x03, 0x00);
+        large.vram = Some(24 << 30);
+        let igpu = MockProvider::new(0x8086, 0xa780, 0x03, 0x00);
+        let unknown = MockProvider::new(0x10de, 0x2684, 0x03, 0x00);
+        let gpus = vec![
+            PciDevice::from_address(0, 0, 2, 0).with_provider(igpu),
+            PciDevice::from_address(0, 5, 0, 0).with_provider(unknown),
+            PciDevice::from_address(0, 4, 0, 0).with_provider(small),
+            PciDevice::from_address(0, 3, 0, 0).with_provider(large),
+            PciDevice::from_address(0, 1, 0, 0).with_provider(unknown),
+        ];
+        let order: Vec<_> = by_vram(gpus)
+            .into_iter()
+            .map(|gpu| gpu.address().slot())
+            .collect();
+        assert_eq!(
+            order,
+            [(0, 3, 0), (0, 4, 0), (0, 1, 0), (0, 5, 0), (0, 0, 2)]
+        );
+    }
+
+    #[test]
+    fn vram_ties() {
+        // Equal VRAM puts the discrete GPU first, even over the boot display
+        let mut apu = MockProvider::new(0x1002, 0x15bf, 0x03, 0x00);
+        apu.vram = Some(512 << 20);
+        apu.boot_vga = true;
+        let mut dgpu = MockProvider::new(0x10de, 0x2860, 0x03, 0x00);
+        dgpu.vram = Some(512 << 20);
+        let gpus = vec![
+            PciDevice::from_address(0, 0, 8, 0).with_provider(apu),
+            PciDevice::from_address(0, 1, 0, 0).with_provider(dgpu),
+        ];
+        let order: Vec<_> = by_vram(gpus)
+            .into_iter()
+            .map(|gpu| gpu.address().slot())
+            .collect();
+        assert_eq!(order, [(0, 1, 0), (0, 0, 8)]);
+
+        // Otherwise equal, the boot display wins
+        let dgpu = MockProvider::new(0x10de, 0x2860, 0x03, 0x00);
+        let mut boot = MockProvider::new(0x10de, 0x2684, 0x03, 0x00);
+        boot.boot_vga = true;
+        let gpus = vec![
+            PciDevice::from_address(0, 1, 0, 0).with_provider(dgpu),
+            PciDevice::from_address(0, 2, 0, 0).with_provider(boot),
+        ];
+        assert_eq!(by_vram(gpus)[0].address().slot(), (0, 2, 0));
+    }
+
+    #[test]
+    fn label_preferred() {
+        let mut dgpu = MockProvider::new(0x1002, 0x744c, 0x03, 0x00);
+        dgpu.label = Some("Radeon Pro W7900");
+        dgpu.driver = Some("amdgpu");
+        let summaries = summarize(vec![PciDevice::from_address(0, 3, 0, 0).with_provider(dgpu)]);
+        assert_eq!(summaries[0].to_string(), "Radeon Pro W7900 [amdgpu]");
+
+        // An empty label falls back to the database name
+        let device = summaries[0].device;
+        let name = PrettyName {
+            device: &device,
+            label: Some(b""),
+            db: &PciIdDb::default(),
+        };
+        assert_eq!(
+            name.to_string(),
+            PrettyDevice(device.device.unwrap()).to_string()
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Grouping of the functions that share a physical device slot, such as a GPU and its HDMI audio.
+use super::{PrettyDevice, ResolvedDevice};
+use std::fmt;
+
+/// How the functions of a [`SlotGroup`] are displayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GroupMode {
+    /// Only the primary function, e.g. `NVIDIA GeForce RTX 4090`.
+    #[default]
+    CollapseToPrimary,
+    /// The primary function followed by the classes of the others,
+    /// e.g. `NVIDIA GeForce RTX 4090 (+ Audio device)`.
+    AllFunctions,
+}
+
+/// All functions found at one `(domain, bus, device)` slot, ordered by function number.
+#[derive(Debug, Clone)]
+pub struct SlotGroup {
+    functions: Vec<ResolvedDevice>,
+}
+
+impl SlotGroup {
+    /// The `(domain, bus, device)` shared by every function.
+    pub fn slot(&self) -> (u16, u8, u8) {
+        self.primary().address.slot()
+    }
+    /// The lowest numbered function, which is function 0 on well-behaved hardware.
+    pub fn primary(&self) -> &ResolvedDevice {
+        &self.functions[0]
+    }
+    /// The functions besides the primary one.
+    pub fn secondary(&self) -> &[ResolvedDevice] {
+        &self.functions[1..]
+    }
+    pub fn functions(&self) -> &[ResolvedDevice] {
+        &self.functions
+    }
+    pub fn display(&self, mode: GroupMode) -> DisplayGroup<'_> {
+        DisplayGroup { group: self, mode }
+    }
+}
+
+/// Created by [`SlotGroup::display`].
+pub struct DisplayGroup<'g> {
+    group: &'g SlotGroup,
+    mode: GroupMode,
+}
+
+impl fmt::Display for DisplayGroup<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let primary = self.group.primary();
+        match primary.device {
+            Some(device) => write!(f, "{}", PrettyDevice(device))?,
+            None => write!(f, "{:04x}:{:04x}", primary.vendor_id, primary.device_id)?,
+        }
+        if self.mode == GroupMode::AllFunctions && !self.group.secondary().is_empty() {
+            f.write_str(" (+ ")?;
+            for (idx, function) in self.group.secondary().iter().enumerate() {
+                if idx != 0 {
+                    f.write_str(", ")?;
+                }
+                f.write_str(function.class_name().unwrap_or("Unknown function"))?;
+            }
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+/// Groups devices by slot, returning the groups ordered by address.
+pub fn group_by_slot(devices: impl IntoIterator<Item = ResolvedDevice>) -> Vec<SlotGroup> {
+    let mut devices: Vec<_> = devices.into_iter().collect();
+    devices.sort_unstable_by_key(|device| device.address);
+    devices
+        .chunk_by(|a, b| a.address.slot() == b.address.slot())
+        .map(|functions| SlotGroup {
+            functions: functions.to_vec(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci::{PciAddress, PciClass};
+
+    fn device(
+        bus: u8,
+        function: u8,
+        class: u8,
+        subclass: u8,
+        vid: u16,
+        did: u16,
+    ) -> ResolvedDevice {
+        ResolvedDevice {
+            address: PciAddress {
+                domain: 0,
+                bus,
+                device: 0,
+                function,
+            },
+            vendor_id: vid,
+            device_id: did,
+            class: PciClass {
+                class,
+                subclass,
+                prog_if: 0,
+            },
+            device: pci_ids::Device::from_vid_pid(vid, did),
+        }
+    }
+
+    #[test]
+    fn same_slot_in_two_domains() {
+        let mut second_domain = device(3, 0, 0x03, 0x00, 0x10de, 0x2684);
+        second_domain.address.domain = 1;
+        let groups = group_by_slot([
+            second_domain,
+            device(3, 1, 0x04, 0x03, 0x10de, 0x22ba),
+            device(3, 0, 0x03, 0x00, 0x10de, 0x2684),
+        ]);
+        let slots: Vec<_> = groups.iter().map(SlotGroup::slot).collect();
+        assert_eq!(slots, [(0, 3, 0), (1, 3, 0)]);
+        assert_eq!(groups[0].secondary().len(), 1);
+        assert!(groups[1].secondary().is_empty());
+    }
+
+    #[test]
+    fn gpu_with_audio() {
+        let groups = group_by_slot([
+            device(3, 1, 0x04, 0x03, 0x10de, 0x22ba),
+            device(4, 0, 0x02, 0x00, 0x8086, 0x125c),
+            device(3, 0, 0x03, 0x00, 0x10de, 0x2684),
+        ]);
+        assert_eq!(groups.len(), 2);
+        let gpu = &groups[0];
+        assert_eq!(gpu.slot(), (0, 3, 0));
+        assert_eq!(gpu.primary().address.function, 0);
+        assert_eq!(gpu.secondary().len(), 1);
+        assert_eq!(
+            gpu.display(GroupMode::CollapseToPrimary).to_string(),
+            PrettyDevice(gpu.primary().device.unwrap()).to_string()
+        );
+        assert!(gpu
+            .display(GroupMode::AllFunctions)
+            .to_string()
+            .ends_with(" (+ Audio device)"));
+    }
+}
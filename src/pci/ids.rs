@@ -0,0 +1,173 @@
+//! A runtime PCI ID database, overlaid on the one compiled into `pci-ids`.
+//!
+//! Distributions ship an up to date `pci.ids`, so reading it lets brand new devices resolve
+//! without waiting for a crate release.
+use super::ResolvedDevice;
+use crate::parse::fixed_hex;
+use pci_ids::FromId;
+use std::{fmt, fs, io, path::Path, sync::OnceLock};
+use winnow::Parser;
+
+/// Where distributions install `pci.ids`, in order of preference.
+const SYSTEM_PATHS: [&str; 3] = [
+    "/usr/share/hwdata/pci.ids",
+    "/usr/share/misc/pci.ids",
+    "/usr/share/pci.ids",
+];
+
+/// Vendor and device names from a `pci.ids` file, falling back to the compiled-in database.
+///
+/// The file is kept as is and only scanned for the IDs that are looked up.
+#[derive(Clone, Default)]
+pub struct PciIdDb {
+    ids: Box<[u8]>,
+}
+
+impl PciIdDb {
+    /// Loads the system `pci.ids`, an absent file yields an empty overlay,
+    /// so every lookup falls back to `pci-ids`.
+    pub fn load_system() -> Self {
+        SYSTEM_PATHS
+            .into_iter()
+            .find_map(|path| Self::load(path).ok())
+            .unwrap_or_default()
+    }
+    /// [`PciIdDb::load_system`], loaded once and shared by every caller.
+    pub fn system() -> &'static Self {
+        static SYSTEM: OnceLock<PciIdDb> = OnceLock::new();
+        SYSTEM.get_or_init(Self::load_system)
+    }
+    /// Loads a `pci.ids` file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::parse(fs::read(path)?))
+    }
+    /// Wraps the contents of a `pci.ids` file. Malformed lines are skipped when looking names up.
+    pub fn parse(buf: impl Into<Box<[u8]>>) -> Self {
+        Self { ids: buf.into() }
+    }
+    pub fn vendor_name(&self, vendor_id: u16) -> Option<&str> {
+        match self.vendor(vendor_id) {
+            Some((name, _)) => Some(name),
+            None => pci_ids::Vendor::from_id(vendor_id).map(pci_ids::Vendor::name),
+        }
+    }
+    pub fn device_name(&self, vendor_id: u16, device_id: u16) -> Option<&str> {
+        let name = self.vendor(vendor_id).and_then(|(_, mut devices)| {
+            devices.find_map(|line| match id_and_name(&line[1..])? {
+                (id, name) if id == device_id => Some(name),
+                _ => None,
+            })
+        });
+        match name {
+            Some(name) => Some(name),
+            None => pci_ids::Device::from_vid_pid(vendor_id, device_id).map(pci_ids::Device::name),
+        }
+    }
+    /// The lines of the vendor and device sections, without comments and blank lines.
+    fn lines(&self) -> impl Iterator<Item = &[u8]> {
+        self.ids
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.trim_ascii().is_empty() && !line.starts_with(b"#"))
+            // The class section follows the devices and isn't needed
+            .take_while(|line| !line.starts_with(b"C "))
+    }
+    /// The name of `vendor_id` and its indented device and subsystem lines.
+    fn vendor(&self, vendor_id: u16) -> Option<(&str, impl Iterator<Item = &[u8]>)> {
+        let mut lines = self.lines();
+        // Indented lines never parse as a vendor
+        let name = lines.find_map(|line| match id_and_name(line)? {
+            (id, name) if id == vendor_id => Some(name),
+            _ => None,
+        })?;
+        Some((name, lines.take_while(|line| line.starts_with(b"\t"))))
+    }
+    /// Displays `device` like [`ResolvedDevice`] does, with names looked up in this database.
+    pub fn display<'a>(&'a self, device: &'a ResolvedDevice) -> impl fmt::Display + 'a {
+        DisplayWithDb { db: self, device }
+    }
+}
+
+/// Parses `hhhh  Name`.
+fn id_and_name(line: &[u8]) -> Option<(u16, &str)> {
+    let (id, name) = line.split_at_checked(4)?;
+    let id = fixed_hex(4).parse(id).ok()? as u16;
+    let name = std::str::from_utf8(name.trim_ascii()).ok()?;
+    Some((id, name))
+}
+
+impl fmt::Debug for PciIdDb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PciIdDb")
+            .field("len", &self.ids.len())
+            .finish()
+    }
+}
+
+struct DisplayWithDb<'a> {
+    db: &'a PciIdDb,
+    device: &'a ResolvedDevice,
+}
+
+impl fmt::Display for DisplayWithDb<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let device = self.device;
+        device.write_line(
+            f,
+            self.db.vendor_name(device.vendor_id),
+            self.db.device_name(device.vendor_id, device.device_id),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDS: &[u8] = b"# pci.ids snippet\n\
+        \n\
+        10de  NVIDIA Corporation\n\
+        \t2684  AD102 [GeForce RTX 4090]\n\
+        \t\t1043 889c  ROG Strix GeForce RTX 4090\n\
+        \tffff  GB999 [GeForce RTX 9090]\n\
+        C 03  Display controller\n\
+        \t00  VGA compatible controller\n";
+
+    #[test]
+    fn overlay() {
+        let db = PciIdDb::parse(IDS);
+        assert_eq!(
+            db.device_name(0x10de, 0xffff),
+            Some("GB999 [GeForce RTX 9090]")
+        );
+        assert_eq!(db.vendor_name(0x10de), Some("NVIDIA Corporation"));
+        // Falls back to the compiled-in database
+        assert!(db.vendor_name(0x8086).is_some());
+        assert_eq!(db.device_name(0xfffe, 0xfffe), None);
+
+        // The subsystem line isn't mistaken for a device, nor the class section for a vendor
+        assert_eq!(db.device_name(0x10de, 0x1043), None);
+        assert!(db.vendor_name(0x0003).is_none());
+
+        let upper = PciIdDb::parse(&b"1AF4  Red Hat, Inc.\n\t104F  Virtio RNG\n"[..]);
+        assert_eq!(upper.device_name(0x1af4, 0x104f), Some("Virtio RNG"));
+    }
+
+    #[test]
+    fn malformed_lines_skipped() {
+        let db = PciIdDb::parse(
+            &b"\tffff  device without a vendor\n\
+            10dg  NVIDIA\n\
+            \t2684  AD102 [GeForce RTX 4090 misattributed]\n\
+            1af4  Red Hat, Inc.\n\
+            \txxxx  not hex\n\
+            \t104f  Virtio RNG\n"[..],
+        );
+        assert_eq!(db.vendor_name(0x1af4), Some("Red Hat, Inc."));
+        assert_eq!(db.device_name(0x1af4, 0x104f), Some("Virtio RNG"));
+        assert_eq!(db.device_name(0x10de, 0xffff), None);
+        assert_eq!(
+            db.device_name(0x10de, 0x2684),
+            pci_ids::Device::from_vid_pid(0x10de, 0x2684).map(pci_ids::Device::name)
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! PCI enumeration through `/proc/bus/pci`, used when sysfs isn't mounted.
+//!
+//! Every device has a file at `/proc/bus/pci/[dddd:]bb/dd.f` exposing its raw config space,
+//! of which unprivileged users can read the first 64 bytes.
+use super::{config_space, PciAddress, PciBackendError, PciClass, PciDevice, PciInfoProvider};
+use crate::{arrayvec::ArrayVec, parse::fixed_hex};
+use std::{
+    fs::{self, File, ReadDir},
+    io::{self, Read, Seek, SeekFrom},
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+use winnow::{
+    combinator::{opt, terminated},
+    Parser,
+};
+
+const PROCFS_ROOT: &str = "/proc/bus/pci";
+/// Overrides [`PROCFS_ROOT`], see the [module docs](super).
+const PROCFS_ROOT_VAR: &str = "RXFETCH_PROCFS_ROOT";
+
+/// Parses a bus directory name, `bb` for domain 0 or `dddd:bb` otherwise.
+fn parse_bus(name: &[u8]) -> Option<(u16, u8)> {
+    (opt(terminated(fixed_hex(4), b':')), fixed_hex(2))
+        .map(|(domain, bus)| (domain.unwrap_or(0) as u16, bus as u8))
+        .parse(name)
+        .ok()
+}
+
+/// Parses a device file name, `dd.f`.
+fn parse_slot(name: &[u8]) -> Option<(u8, u8)> {
+    (fixed_hex(2), b'.', fixed_hex(1))
+        .verify(|&(device, _, function)| device < 32 && function < 8)
+        .map(|(device, _, function)| (device as u8, function as u8))
+        .parse(name)
+        .ok()
+}
+
+pub struct ProcBusBackend {
+    buses: ReadDir,
+    /// The bus directory currently being walked, with its domain and bus number.
+    current: Option<(ReadDir, u16, u8)>,
+    /// Whether providers are created with [`ProcBusProvider::open_lazy`].
+    lazy: bool,
+}
+
+impl ProcBusBackend {
+    /// Enumerates `/proc/bus/pci`, or `$RXFETCH_PROCFS_ROOT` if set.
+    pub fn try_init() -> Result<Self, PciBackendError> {
+        Self::try_init_at(super::backend_root(PROCFS_ROOT_VAR, PROCFS_ROOT))
+    }
+    /// Enumerates the bus directories under `root` instead of `/proc/bus/pci`.
+    pub fn try_init_at(root: impl Into<PathBuf>) -> Result<Self, PciBackendError> {
+        let buses = fs::read_dir(root.into()).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => PciBackendError::NotAvailable,
+            _ => err.into(),
+        })?;
+        Ok(Self {
+            buses,
+            current: None,
+            lazy: false,
+        })
+    }
+    /// Creates providers that only read the parts of the config space header that are asked for,
+    /// see [`ProcBusProvider::open_lazy`].
+    ///
+    /// Worth it when only a few fields of each device are needed, e.g. when filtering by class.
+    pub fn lazy(mut self) -> Self {
+        self.lazy = true;
+        self
+    }
+}
+
+impl Iterator for ProcBusBackend {
+    type Item = Result<PciDevice<ProcBusProvider>, PciBackendError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((devices, domain, bus)) = &mut self.current {
+                match devices.next() {
+                    Some(Ok(entry)) => {
+                        let Some((device, function)) = parse_slot(entry.file_name().as_bytes())
+                        else {
+                            return Some(Err(PciBackendError::InvalidDevice));
+                        };
+                        let address = PciAddress {
+                            domain: *domain,
+                            bus: *bus,
+                            device,
+                            function,
+                        };
+                        let provider = if self.lazy {
+                            ProcBusProvider::open_lazy(entry.path())
+                        } else {
+                            ProcBusProvider::from_devfile(entry.path())
+                        };
+                        return Some(
+                            provider
+                                .map(|provider| PciDevice::new(address).with_provider(provider)),
+                        );
+                    }
+                    Some(Err(err)) => return Some(Err(err.into())),
+                    None => self.current = None,
+                }
+            }
+            let entry = match self.buses.next()? {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err.into())),
+            };
+            // Skip the `devices` summary file
+            if !entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                continue;
+            }
+            let Some((domain, bus)) = parse_bus(entry.file_name().as_bytes()) else {
+                return Some(Err(PciBackendError::InvalidDevice));
+            };
+            match fs::read_dir(entry.path()) {
+                Ok(devices) => self.current = Some((devices, domain, bus)),
+                Err(err) => return Some(Err(err.into())),
+            }
+        }
+    }
+}
+
+/// Reads device information from a copy of its config space header.
+///
+/// The copy is inline, and a lazy provider only holds the device file besides it,
+/// so the provider is `Send` and `Sync`.
+#[derive(Debug)]
+pub struct ProcBusProvider {
+    config: ArrayVec<u8, 64>,
+    /// The device file's path, while the header hasn't been read in full.
+    path: Option<PathBuf>,
+    /// The open device file, if `path` has been opened.
+    /// Its position is always at the end of what `config` holds.
+    file: Option<File>,
+}
+
+/// A clone reopens the device file when it needs more of the header,
+/// so the two never share a file position.
+impl Clone for ProcBusProvider {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            path: self.path.clone(),
+            file: None,
+        }
+    }
+}
+
+impl ProcBusProvider {
+    /// Reads the config space header from a `/proc/bus/pci` device file.
+    pub fn from_devfile(path: impl AsRef<Path>) -> Result<Self, PciBackendError> {
+        let mut provider = Self::open_lazy(path)?;
+        provider.header(64)?;
+        provider.path = None;
+        provider.file = None;
+        Ok(provider)
+    }
+    /// Opens a `/proc/bus/pci` device file, reading the header only as far as each field needs.
+    ///
+    /// E.g. the vendor and device IDs take the first 4 bytes, the class the first 12.
+    pub fn open_lazy(path: impl AsRef<Path>) -> Result<Self, PciBackendError> {
+        let path = path.as_ref();
+        Ok(Self {
+            config: ArrayVec::new(),
+            file: Some(File::open(path)?),
+            path: Some(path.to_path_buf()),
+        })
+    }
+    /// The header up to at least `end`, or shorter if the file is.
+    fn header(&mut self, end: usize) -> Result<&[u8], PciBackendError> {
+        let missing = end.saturating_sub(self.config.len());
+        if let (Some(path), true) = (&self.path, missing > 0) {
+            let file = match &mut self.file {
+                Some(file) => file,
+                None => {
+                    let mut file = File::open(path)?;
+                    file.seek(SeekFrom::Start(self.config.len() as u64))?;
+                    self.file.insert(file)
+                }
+            };
+            io::copy(&mut file.take(missing as u64), &mut self.config)?;
+            // Nothing left to read, so don't hold on to the file
+            if self.config.is_full() {
+                self.file = None;
+                self.path = None;
+            }
+        }
+        Ok(&self.config)
+    }
+}
+
+impl PciInfoProvider for ProcBusProvider {
+    fn get_vendor(&mut self) -> Result<u16, PciBackendError> {
+        config_space::read_u16(self.header(0x02)?, 0x00)
+    }
+    fn get_device(&mut self) -> Result<u16, PciBackendError> {
+        config_space::read_u16(self.header(0x04)?, 0x02)
+    }
+    fn get_class(&mut self) -> Result<PciClass, PciBackendError> {
+        config_space::read_class(self.header(0x0c)?)
+    }
+    fn get_subsystem_vid(&mut self) -> Result<u16, PciBackendError> {
+        config_space::read_subsystem(self.header(0x30)?, 0x2c)
+    }
+    fn get_subsystem_did(&mut self) -> Result<u16, PciBackendError> {
+        config_space::read_subsystem(self.header(0x30)?, 0x2e)
+    }
+    /// As read when the device was enumerated, or when first asked for by a lazy provider.
+    fn command_register(&mut self) -> Result<u16, PciBackendError> {
+        config_space::read_u16(self.header(0x06)?, 0x04)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn names() {
+        assert_eq!(parse_bus(b"03"), Some((0, 3)));
+        assert_eq!(parse_bus(b"0001:c3"), Some((1, 0xc3)));
+        assert_eq!(parse_bus(b"devices"), None);
+        assert_eq!(parse_slot(b"1f.3"), Some((0x1f, 3)));
+        assert_eq!(parse_slot(b"00.8"), None);
+    }
+
+    #[test]
+    fn lazy_header() {
+        let dir = TempDir::new("procfs");
+        let path = dir.join("config");
+        // A VGA controller with a type 0 header, subsystem 1043:8877
+        let mut config = [0u8; 64];
+        config[..4].copy_from_slice(&[0x02, 0x10, 0x4c, 0x74]);
+        config[0x0b] = 0x03;
+        config[0x2c..0x30].copy_from_slice(&[0x43, 0x10, 0x77, 0x88]);
+        fs::write(&path, config).unwrap();
+
+        let mut lazy = ProcBusProvider::open_lazy(&path).unwrap();
+        assert_eq!(lazy.get_vendor().unwrap(), 0x1002);
+        assert_eq!(lazy.config.len(), 2);
+        assert_eq!(lazy.get_class().unwrap().class, 0x03);
+        assert_eq!(lazy.get_device().unwrap(), 0x744c);
+        assert_eq!(lazy.config.len(), 12);
+        // The clone continues from its own copy of the file
+        let mut clone = lazy.clone();
+        assert_eq!(clone.get_subsystem_vid().unwrap(), 0x1043);
+        assert_eq!(clone.config.len(), 48);
+        assert_eq!(lazy.config.len(), 12);
+        assert_eq!(lazy.get_subsystem_vid().unwrap(), 0x1043);
+        assert_eq!(lazy.get_subsystem_did().unwrap(), 0x8877);
+        assert!(lazy.file.is_some());
+        assert_eq!(lazy.header(64).unwrap(), config);
+        assert!(lazy.file.is_none() && lazy.path.is_none());
+
+        let mut eager = ProcBusProvider::from_devfile(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(eager.config.len(), 64);
+        assert_eq!(eager.get_subsystem_did().unwrap(), 0x8877);
+        assert_eq!(eager.get_device().unwrap(), 0x744c);
+    }
+}
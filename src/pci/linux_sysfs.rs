@@ -0,0 +1,834 @@
+//! PCI enumeration through `/sys/bus/pci/devices`.
+use super::{
+    config_space, gpu::ConnectorInfo, DeviceLabel, PciAddress, PciBackendError, PciClass,
+    PciDevice, PciInfoProvider,
+};
+use crate::{
+    arrayvec::ArrayVec,
+    display::DisplayBytes,
+    parse::{decimal, fixed_hex, sysfs_decimal, sysfs_hex, sysfs_signed},
+    path::WrapPath,
+};
+use std::{
+    fs::{self, File, ReadDir},
+    io::{self, Read},
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+use winnow::{
+    ascii::{float, space1},
+    combinator::{opt, terminated},
+    error::ContextError,
+    Parser,
+};
+
+const SYSFS_ROOT: &str = "/sys/bus/pci/devices";
+/// Overrides [`SYSFS_ROOT`], see the [module docs](super).
+const SYSFS_ROOT_VAR: &str = "RXFETCH_SYSFS_ROOT";
+const DRM_ROOT: &str = "/sys/class/drm";
+/// Overrides [`DRM_ROOT`], see the [module docs](super).
+const DRM_ROOT_VAR: &str = "RXFETCH_DRM_ROOT";
+const DEV_DRI: &str = "/dev/dri";
+
+pub(super) fn root() -> PathBuf {
+    super::backend_root(SYSFS_ROOT_VAR, SYSFS_ROOT)
+}
+
+/// Parses a device directory name such as `0000:03:00.0`.
+pub fn parse_device(name: &[u8]) -> Option<PciAddress> {
+    (
+        fixed_hex(4),
+        b':',
+        fixed_hex(2),
+        b':',
+        fixed_hex(2),
+        b'.',
+        fixed_hex(1),
+    )
+        .verify(|&(_, _, _, _, device, _, function)| device < 32 && function < 8)
+        .map(|(domain, _, bus, _, device, _, function)| PciAddress {
+            domain: domain as u16,
+            bus: bus as u8,
+            device: device as u8,
+            function: function as u8,
+        })
+        .parse(name)
+        .ok()
+}
+
+pub struct SysBusBackend {
+    dir: ReadDir,
+}
+
+impl SysBusBackend {
+    /// Enumerates `/sys/bus/pci/devices`, or `$RXFETCH_SYSFS_ROOT` if set.
+    pub fn try_init() -> Result<Self, PciBackendError> {
+        Self::try_init_at(root())
+    }
+    /// Enumerates the device directories under `root` instead of `/sys/bus/pci/devices`.
+    pub fn try_init_at(root: impl Into<PathBuf>) -> Result<Self, PciBackendError> {
+        let dir = fs::read_dir(root.into()).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => PciBackendError::NotAvailable,
+            _ => err.into(),
+        })?;
+        Ok(Self { dir })
+    }
+    /// Opens the device at `address` without enumerating the bus.
+    pub fn device_at(address: PciAddress) -> Result<PciDevice<SysBusProvider>, PciBackendError> {
+        Self::device_at_in(root(), address)
+    }
+    /// Like [`Self::device_at`], looking under `root` instead of `/sys/bus/pci/devices`.
+    pub fn device_at_in(
+        root: impl Into<PathBuf>,
+        address: PciAddress,
+    ) -> Result<PciDevice<SysBusProvider>, PciBackendError> {
+        let mut path = root.into();
+        path.push(address.to_string());
+        if !path.is_dir() {
+            return Err(PciBackendError::InvalidDevice);
+        }
+        Ok(PciDevice::new(address).with_provider(SysBusProvider::new(path)))
+    }
+}
+
+/// Reads the attribute `attr` of the device at `bdf`, e.g. `query("0000:03:00.0", "current_link_speed")`.
+///
+/// See [`SysBusProvider::read_attr`] for how the value is read. A malformed address,
+/// or one with no device behind it, fails with [`PciBackendError::InvalidDevice`].
+pub fn query(bdf: &str, attr: &str) -> Result<ArrayVec<u8, 256>, PciBackendError> {
+    query_in(root(), bdf, attr)
+}
+
+fn query_in(
+    root: impl Into<PathBuf>,
+    bdf: &str,
+    attr: &str,
+) -> Result<ArrayVec<u8, 256>, PciBackendError> {
+    let address = parse_device(bdf.as_bytes()).ok_or(PciBackendError::InvalidDevice)?;
+    SysBusBackend::device_at_in(root, address)?
+        .provider()
+        .read_attr(attr)
+}
+
+impl Iterator for SysBusBackend {
+    type Item = Result<PciDevice<SysBusProvider>, PciBackendError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = match self.dir.next()? {
+            Ok(entry) => entry,
+            Err(err) => return Some(Err(err.into())),
+        };
+        let Some(address) = parse_device(entry.file_name().as_bytes()) else {
+            return Some(Err(PciBackendError::InvalidDevice));
+        };
+        Some(Ok(
+            PciDevice::new(address).with_provider(SysBusProvider::new(entry.path()))
+        ))
+    }
+}
+
+/// The PCI devices behind the DRM cards in `/sys/class/drm`, or `$RXFETCH_DRM_ROOT` if set,
+/// ordered by address.
+///
+/// This only touches display adapters with a loaded driver,
+/// which is much cheaper than walking every device on the bus.
+pub fn drm_devices() -> Vec<PciDevice<SysBusProvider>> {
+    drm_devices_at(super::backend_root(DRM_ROOT_VAR, DRM_ROOT))
+}
+
+/// Like [`drm_devices`], looking under `root` instead of `/sys/class/drm`.
+pub fn drm_devices_at(root: impl AsRef<Path>) -> Vec<PciDevice<SysBusProvider>> {
+    let Ok(dir) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<_> = dir
+        .flatten()
+        .filter(|entry| {
+            // Connectors such as `card0-DP-1` and render nodes also live here
+            let name = entry.file_name();
+            let digits = name.as_bytes().strip_prefix(b"card").unwrap_or_default();
+            !digits.is_empty() && digits.iter().all(u8::is_ascii_digit)
+        })
+        .filter_map(|entry| {
+            // `device` links to the card's parent, which isn't a PCI device on e.g. SoCs
+            let path = fs::canonicalize(entry.path().join("device")).ok()?;
+            let address = parse_device(path.file_name()?.as_bytes())?;
+            Some(PciDevice::new(address).with_provider(SysBusProvider::new(path)))
+        })
+        .collect();
+    devices.sort_unstable_by_key(PciDevice::address);
+    devices.dedup_by_key(|device| device.address());
+    devices
+}
+
+/// Reads device attributes from its sysfs directory.
+///
+/// Only holds the path, so it's `Send` and `Sync` and devices can be read from any thread.
+#[derive(Debug, Clone)]
+pub struct SysBusProvider {
+    path: PathBuf,
+}
+
+impl SysBusProvider {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+    /// Reads a short attribute file, such as `vendor`.
+    fn read_short(&mut self, attr: &str) -> Result<ArrayVec<u8, 16>, PciBackendError> {
+        let path = WrapPath::new(&mut self.path, attr);
+        Ok(read_to_end(File::open(&*path)?)?)
+    }
+    /// Like [`Self::read_short`], but a missing or unreadable attribute yields `None`,
+    /// so optional fields don't make the whole device fail.
+    fn read_optional(&mut self, attr: &str) -> Result<Option<ArrayVec<u8, 16>>, PciBackendError> {
+        match self.read_short(attr) {
+            Ok(buf) => Ok(Some(buf)),
+            Err(PciBackendError::AccessDenied) => Ok(None),
+            Err(PciBackendError::IOError(err)) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+    /// Reads any attribute in the device directory, such as `power_state` or `d3cold_allowed`,
+    /// with surrounding whitespace trimmed.
+    ///
+    /// Attributes longer than 256 bytes fail with an [`io::ErrorKind::WriteZero`] error
+    /// instead of being truncated.
+    pub fn read_attr(&mut self, name: &str) -> Result<ArrayVec<u8, 256>, PciBackendError> {
+        if name.contains('/') || name == ".." {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "attribute names can't contain path separators",
+            )
+            .into());
+        }
+        let path = WrapPath::new(&mut self.path, name);
+        let buf: ArrayVec<u8, 256> = read_to_end(File::open(&*path)?)?;
+        let mut trimmed = ArrayVec::new();
+        trimmed.copy_from_slice(buf.trim_ascii());
+        Ok(trimmed)
+    }
+    /// Reads `attr` from the first of the device's hwmon directories that has it.
+    fn read_hwmon(&mut self, attr: &str) -> Option<ArrayVec<u8, 16>> {
+        let mut hwmon = WrapPath::new(&mut self.path, "hwmon");
+        let entries = fs::read_dir(&*hwmon).ok()?;
+        entries.flatten().find_map(|entry| {
+            let mut input = hwmon.push(entry.file_name());
+            let input = input.push(attr);
+            read_to_end(File::open(&*input).ok()?).ok()
+        })
+    }
+    fn read_u16(&mut self, attr: &str) -> Result<u16, PciBackendError> {
+        parse_u16_hex(&self.read_short(attr)?).ok_or(PciBackendError::InvalidDevice)
+    }
+}
+
+/// Parses `current_link_speed` in GT/s, e.g. `16.0 GT/s PCIe` or `2.5 GT/s` on older kernels.
+fn parse_link_speed(buf: &[u8]) -> Option<f32> {
+    terminated(
+        float::<_, _, ContextError>,
+        (space1, b"GT/s", opt((space1, b"PCIe")), opt(b'\n')),
+    )
+    .parse(buf)
+    .ok()
+}
+
+/// Parses `current_link_width`, the number of lanes, e.g. `16`.
+fn parse_link_width(buf: &[u8]) -> Option<u8> {
+    terminated(decimal, opt(b'\n')).parse(buf).ok()
+}
+
+/// Parses the first line of a connector's `modes`, the preferred one, e.g. `3840x2160`.
+///
+/// Interlaced modes carry an `i` suffix, which is ignored.
+fn parse_preferred_mode(modes: &[u8]) -> Option<(u32, u32)> {
+    let line = modes.split(|&b| b == b'\n').next()?;
+    let (width, _, height) = (decimal, b'x', decimal).parse_next(&mut &line[..]).ok()?;
+    Some((width, height))
+}
+
+/// Finds the active state in a power-play clock table such as `pp_dpm_sclk`,
+/// the one marked with `*`:
+///
+/// ```text
+/// 0: 500Mhz
+/// 1: 2500Mhz *
+/// ```
+fn parse_active_clock(states: &[u8]) -> Option<u32> {
+    states.split(|&b| b == b'\n').find_map(|line| {
+        let state = line.trim_ascii_end().strip_suffix(b"*")?;
+        let (_, clock) = state.split_at(state.iter().position(|&b| b == b':')? + 1);
+        let clock = clock.trim_ascii();
+        let digits = clock.iter().take_while(|b| b.is_ascii_digit()).count();
+        clock[digits..]
+            .eq_ignore_ascii_case(b"mhz")
+            .then(|| decimal.parse(&clock[..digits]).ok())?
+    })
+}
+
+/// Parses `gpu_busy_percent`, e.g. `12`.
+fn parse_percent(buf: &[u8]) -> Option<u8> {
+    terminated(decimal, opt(b'\n'))
+        .verify(|&percent| percent <= 100)
+        .parse(buf)
+        .ok()
+}
+
+/// Parses an hwmon `temp*_input` attribute in millidegrees, e.g. `54000`, into °C.
+fn parse_millidegrees(buf: &[u8]) -> Option<f32> {
+    terminated(decimal::<u32>, opt(b'\n'))
+        .parse(buf)
+        .ok()
+        .map(|millis| millis as f32 / 1000.0)
+}
+
+/// Parses an hwmon `power*_average` attribute in microwatts, e.g. `180000000`, into watts.
+fn parse_microwatts(buf: &[u8]) -> Option<f32> {
+    sysfs_decimal::<u64>(buf).map(|micros| micros as f32 / 1_000_000.0)
+}
+
+/// Reads until EOF, so short reads can't leave part of the buffer unfilled.
+fn read_to_end<const CAP: usize>(mut reader: impl Read) -> io::Result<ArrayVec<u8, CAP>> {
+    let mut buf = ArrayVec::new();
+    io::copy(&mut reader, &mut buf)?;
+    Ok(buf)
+}
+
+/// Parses a 16-bit ID attribute such as `vendor` or `subsystem_device`, e.g. `0x10de\n`.
+pub fn parse_u16_hex(buf: &[u8]) -> Option<u16> {
+    sysfs_hex(buf, 4).map(|id| id as u16)
+}
+
+impl PciInfoProvider for SysBusProvider {
+    fn get_vendor(&mut self) -> Result<u16, PciBackendError> {
+        self.read_u16("vendor")
+    }
+    fn get_device(&mut self) -> Result<u16, PciBackendError> {
+        self.read_u16("device")
+    }
+    fn get_class(&mut self) -> Result<PciClass, PciBackendError> {
+        let class =
+            sysfs_hex(&self.read_short("class")?, 6).ok_or(PciBackendError::InvalidDevice)?;
+        let [_, class, subclass, prog_if] = class.to_be_bytes();
+        Ok(PciClass {
+            class,
+            subclass,
+            prog_if,
+        })
+    }
+    fn get_subsystem_vid(&mut self) -> Result<u16, PciBackendError> {
+        self.read_u16("subsystem_vendor")
+    }
+    fn get_subsystem_did(&mut self) -> Result<u16, PciBackendError> {
+        self.read_u16("subsystem_device")
+    }
+    /// Reads the start of the binary `config` attribute, the first 64 bytes of which are
+    /// readable without privileges.
+    fn command_register(&mut self) -> Result<u16, PciBackendError> {
+        let path = WrapPath::new(&mut self.path, "config");
+        let header: ArrayVec<u8, 6> = read_to_end(File::open(&*path)?.take(6))?;
+        config_space::read_u16(&header, 0x04)
+    }
+    /// Only VGA devices have a `boot_vga` attribute, so a missing file means `false`.
+    fn is_boot_vga(&mut self) -> Result<bool, PciBackendError> {
+        match self.read_optional("boot_vga")?.as_deref() {
+            None | Some(b"0\n" | b"0") => Ok(false),
+            Some(b"1\n" | b"1") => Ok(true),
+            Some(_) => Err(PciBackendError::InvalidDevice),
+        }
+    }
+    /// Non-PCIe devices have no link attributes, and a link that's down reports an unknown speed,
+    /// both yield `None`.
+    fn link_status(&mut self) -> Result<Option<(f32, u8)>, PciBackendError> {
+        let Some(speed) = self.read_optional("current_link_speed")? else {
+            return Ok(None);
+        };
+        let Some(width) = self.read_optional("current_link_width")? else {
+            return Ok(None);
+        };
+        if speed.starts_with(b"Unknown") {
+            return Ok(None);
+        }
+        let speed = parse_link_speed(&speed).ok_or(PciBackendError::InvalidDevice)?;
+        let width = parse_link_width(&width).ok_or(PciBackendError::InvalidDevice)?;
+        Ok(Some((speed, width)))
+    }
+    /// Single-node systems report `-1`, which yields `None`, as do kernels without NUMA support.
+    fn numa_node(&mut self) -> Result<Option<i32>, PciBackendError> {
+        let Some(node) = self.read_optional("numa_node")? else {
+            return Ok(None);
+        };
+        match sysfs_signed::<i32>(&node) {
+            Some(-1) => Ok(None),
+            Some(node) if node >= 0 => Ok(Some(node)),
+            _ => Err(PciBackendError::InvalidDevice),
+        }
+    }
+    /// Finds the `renderD*` entry in the device's `drm` directory, which the DRM driver creates
+    /// alongside the `card*` entry. The node has the same name under `/dev/dri`.
+    fn render_node(&mut self) -> Result<Option<PathBuf>, PciBackendError> {
+        let drm = WrapPath::new(&mut self.path, "drm");
+        let entries = match fs::read_dir(&*drm) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        for entry in entries {
+            let name = entry?.file_name();
+            if name.as_bytes().starts_with(b"renderD") {
+                return Ok(Some(Path::new(DEV_DRI).join(name)));
+            }
+        }
+        Ok(None)
+    }
+    /// Reads the connectors under each `drm/cardN` of the device, e.g. `drm/card1/card1-DP-1`,
+    /// keeping those whose `status` is `connected`. Ordered by name.
+    fn connectors(&mut self) -> Result<Vec<ConnectorInfo>, PciBackendError> {
+        let drm = WrapPath::new(&mut self.path, "drm");
+        let cards = match fs::read_dir(&*drm) {
+            Ok(cards) => cards,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut connectors = Vec::new();
+        for card in cards {
+            let card = card?;
+            let card_name = card.file_name();
+            // Skip render nodes
+            if !card_name.as_bytes().starts_with(b"card") {
+                continue;
+            }
+            for entry in fs::read_dir(card.path())? {
+                let entry = entry?;
+                let file_name = entry.file_name();
+                let Some(name) = file_name
+                    .as_bytes()
+                    .strip_prefix(card_name.as_bytes())
+                    .and_then(|name| name.strip_prefix(b"-"))
+                else {
+                    continue;
+                };
+                let status = fs::read(entry.path().join("status")).unwrap_or_default();
+                if status.trim_ascii() != b"connected" {
+                    continue;
+                }
+                let modes = fs::read(entry.path().join("modes")).unwrap_or_default();
+                connectors.push(ConnectorInfo {
+                    name: String::from_utf8_lossy(name).into_owned(),
+                    preferred_mode: parse_preferred_mode(&modes),
+                });
+            }
+        }
+        connectors.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        Ok(connectors)
+    }
+    /// Reads `label`, which the kernel creates from the ACPI `_DSM` device name or the SMBIOS
+    /// onboard device information. Usually absent.
+    fn label(&mut self) -> Result<Option<DeviceLabel>, PciBackendError> {
+        match self.read_attr("label") {
+            Ok(label) if label.is_empty() => Ok(None),
+            Ok(label) => Ok(Some(DisplayBytes(label))),
+            Err(PciBackendError::AccessDenied) => Ok(None),
+            Err(PciBackendError::IOError(err)) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+    /// The name of the module the `driver` link points to.
+    fn driver(&mut self) -> Option<String> {
+        let link = WrapPath::new(&mut self.path, "driver");
+        let driver = fs::read_link(&*link).ok()?;
+        Some(driver.file_name()?.to_string_lossy().into_owned())
+    }
+    /// Reads `mem_info_vram_total`, which only `amdgpu` provides.
+    fn vram_bytes(&mut self) -> Option<u64> {
+        let total = self.read_optional("mem_info_vram_total").ok()??;
+        sysfs_decimal(&total)
+    }
+    /// Reads `temp1_input` of the first hwmon device registered by the driver,
+    /// the same directory `/sys/class/drm/card*/device` links to.
+    fn temperature(&mut self) -> Option<f32> {
+        parse_millidegrees(&self.read_hwmon("temp1_input")?)
+    }
+    /// Reads `power1_average` from hwmon like [`Self::temperature`], falling back to
+    /// `power1_input`, which newer `amdgpu` GPUs report instead.
+    fn power_draw(&mut self) -> Result<Option<f32>, PciBackendError> {
+        let Some(power) = self
+            .read_hwmon("power1_average")
+            .or_else(|| self.read_hwmon("power1_input"))
+        else {
+            return Ok(None);
+        };
+        parse_microwatts(&power)
+            .map(Some)
+            .ok_or(PciBackendError::InvalidDevice)
+    }
+    /// Reads the active state of `pp_dpm_sclk`, which only `amdgpu` provides.
+    fn core_clock_mhz(&mut self) -> Option<u32> {
+        let states = fs::read(&*WrapPath::new(&mut self.path, "pp_dpm_sclk")).ok()?;
+        parse_active_clock(&states)
+    }
+    /// Reads the active state of `pp_dpm_mclk`, which only `amdgpu` provides.
+    fn mem_clock_mhz(&mut self) -> Option<u32> {
+        let states = fs::read(&*WrapPath::new(&mut self.path, "pp_dpm_mclk")).ok()?;
+        parse_active_clock(&states)
+    }
+    /// Reads `gpu_busy_percent`, which only `amdgpu` provides.
+    fn utilization(&mut self) -> Option<u8> {
+        let busy = self.read_optional("gpu_busy_percent").ok()??;
+        parse_percent(&busy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci::{pcie_generation, PciDevIterBackend};
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn device_name() {
+        assert_eq!(
+            parse_device(b"0001:c3:1f.7"),
+            Some(PciAddress {
+                domain: 1,
+                bus: 0xc3,
+                device: 0x1f,
+                function: 7
+            })
+        );
+        assert_eq!(parse_device(b"0000:03:20.0"), None);
+        assert_eq!(parse_device(b"0000:03:00.0/"), None);
+    }
+
+    #[test]
+    fn u16_attributes() {
+        assert_eq!(parse_u16_hex(b"0x10de\n"), Some(0x10de));
+        assert_eq!(parse_u16_hex(b"0x1002"), Some(0x1002));
+        assert_eq!(parse_u16_hex(b"0xffff\n"), Some(0xffff));
+        assert_eq!(parse_u16_hex(b"10de\n"), None);
+        assert_eq!(parse_u16_hex(b"0x10d\n"), None);
+        assert_eq!(parse_u16_hex(b"0x10de0\n"), None);
+        assert_eq!(parse_u16_hex(b"0x10de\n\n"), None);
+    }
+
+    #[test]
+    fn link_status() {
+        assert_eq!(parse_link_speed(b"16.0 GT/s PCIe\n"), Some(16.0));
+        assert_eq!(parse_link_speed(b"2.5 GT/s PCIe\n"), Some(2.5));
+        assert_eq!(parse_link_speed(b"8 GT/s\n"), Some(8.0));
+        assert_eq!(parse_link_speed(b"5.0 GT/s"), Some(5.0));
+        assert_eq!(parse_link_speed(b"Unknown speed\n"), None);
+        assert_eq!(parse_link_width(b"16\n"), Some(16));
+        assert_eq!(parse_link_width(b"x16\n"), None);
+        assert_eq!(pcie_generation(16.0), Some(4));
+        assert_eq!(pcie_generation(2.5), Some(1));
+        assert_eq!(pcie_generation(3.0), None);
+    }
+
+    #[test]
+    fn providers_are_thread_safe() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SysBusProvider>();
+        assert_send_sync::<crate::pci::linux_procfs::ProcBusProvider>();
+        assert_send_sync::<crate::pci::AutoProvider>();
+        assert_send_sync::<PciDevice<crate::pci::AutoProvider>>();
+        assert_send_sync::<crate::pci::PciAutoIter>();
+    }
+
+    #[test]
+    fn active_clock() {
+        assert_eq!(
+            parse_active_clock(b"S: 19Mhz\n0: 500Mhz\n1: 1834Mhz *\n2: 2500Mhz\n"),
+            Some(1834)
+        );
+        // Sleeping
+        assert_eq!(parse_active_clock(b"S: 19Mhz *\n0: 500Mhz\n"), Some(19));
+        assert_eq!(parse_active_clock(b"0: 96MHz *\n"), Some(96));
+        assert_eq!(parse_active_clock(b"0: 500Mhz\n1: 2500Mhz\n"), None);
+        assert_eq!(parse_active_clock(b"0: fast *\n"), None);
+        assert_eq!(parse_active_clock(b""), None);
+    }
+
+    #[test]
+    fn gpu_stats() {
+        let dir = TempDir::new("gpu-stats");
+        fs::create_dir_all(dir.join("hwmon/hwmon3")).unwrap();
+        fs::write(dir.join("hwmon/hwmon3/temp1_input"), "54000\n").unwrap();
+        fs::write(dir.join("gpu_busy_percent"), "12\n").unwrap();
+        fs::write(dir.join("numa_node"), "1\n").unwrap();
+        fs::write(dir.join("mem_info_vram_total"), "25753026560\n").unwrap();
+        std::os::unix::fs::symlink("../../../bus/pci/drivers/amdgpu", dir.join("driver")).unwrap();
+
+        let mut provider = SysBusProvider::new(&dir);
+        assert_eq!(provider.vram_bytes(), Some(25753026560));
+        assert_eq!(provider.driver().as_deref(), Some("amdgpu"));
+        assert!(provider.label().unwrap().is_none());
+        fs::write(dir.join("label"), " Radeon Pro W7900 Dual Slot\n").unwrap();
+        assert_eq!(
+            provider.label().unwrap().unwrap().to_string(),
+            "Radeon Pro W7900 Dual Slot"
+        );
+        assert_eq!(provider.temperature(), Some(54.0));
+        assert_eq!(provider.core_clock_mhz(), None);
+        fs::write(dir.join("pp_dpm_sclk"), "0: 500Mhz\n1: 2500Mhz *\n").unwrap();
+        fs::write(dir.join("pp_dpm_mclk"), "0: 96Mhz \n1: 1250Mhz\n").unwrap();
+        assert_eq!(provider.core_clock_mhz(), Some(2500));
+        assert_eq!(provider.mem_clock_mhz(), None);
+        assert_eq!(provider.power_draw().unwrap(), None);
+        fs::write(dir.join("hwmon/hwmon3/power1_input"), "212000000\n").unwrap();
+        assert_eq!(provider.power_draw().unwrap(), Some(212.0));
+        fs::write(dir.join("hwmon/hwmon3/power1_average"), "180500000\n").unwrap();
+        assert_eq!(provider.power_draw().unwrap(), Some(180.5));
+        assert_eq!(provider.utilization(), Some(12));
+        assert_eq!(provider.numa_node().unwrap(), Some(1));
+        fs::write(dir.join("numa_node"), "-1\n").unwrap();
+        assert_eq!(provider.numa_node().unwrap(), None);
+        fs::write(
+            dir.join("config"),
+            [0x02, 0x10, 0x4c, 0x74, 0x06, 0x04, 0x10, 0x00],
+        )
+        .unwrap();
+        assert_eq!(provider.command_register().unwrap(), 0x0406);
+        fs::write(dir.join("config"), [0x02, 0x10, 0x4c, 0x74, 0x06]).unwrap();
+        assert!(matches!(
+            provider.command_register(),
+            Err(PciBackendError::InvalidDevice)
+        ));
+        assert_eq!(provider.path, *dir);
+
+        let mut missing = SysBusProvider::new(dir.join("hwmon/hwmon3"));
+        assert_eq!(missing.temperature(), None);
+        assert_eq!(missing.power_draw().unwrap(), None);
+        assert_eq!(missing.utilization(), None);
+        assert_eq!(missing.vram_bytes(), None);
+        assert_eq!(missing.driver(), None);
+    }
+
+    #[test]
+    fn permission_denied() {
+        assert!(matches!(
+            PciBackendError::from(io::Error::from(io::ErrorKind::PermissionDenied)),
+            PciBackendError::AccessDenied
+        ));
+        assert!(matches!(
+            PciBackendError::from(io::Error::from(io::ErrorKind::NotFound)),
+            PciBackendError::IOError(_)
+        ));
+
+        // Root reads files regardless of their mode
+        if unsafe { libc::geteuid() } == 0 {
+            eprintln!("skipping the unreadable attribute check, running as root");
+            return;
+        }
+        let dir = TempDir::new("denied");
+        fs::write(dir.join("numa_node"), "1\n").unwrap();
+        fs::write(dir.join("current_link_speed"), "16.0 GT/s PCIe\n").unwrap();
+        fs::write(dir.join("current_link_width"), "8\n").unwrap();
+        let denied = dir.join("boot_vga");
+        fs::write(&denied, "1\n").unwrap();
+        let mut perms = fs::metadata(&denied).unwrap().permissions();
+        std::os::unix::fs::PermissionsExt::set_mode(&mut perms, 0o000);
+        fs::set_permissions(&denied, perms).unwrap();
+
+        // The denied attribute reads as missing, and the ones after it are still read
+        let mut provider = SysBusProvider::new(&dir);
+        assert!(matches!(
+            provider.read_short("boot_vga"),
+            Err(PciBackendError::AccessDenied)
+        ));
+        assert!(!provider.is_boot_vga().unwrap());
+        assert_eq!(provider.numa_node().unwrap(), Some(1));
+        assert_eq!(provider.link_status().unwrap(), Some((16.0, 8)));
+    }
+
+    #[test]
+    fn arbitrary_attributes() {
+        let dir = TempDir::new("read-attr");
+        fs::write(dir.join("power_state"), "D0\n").unwrap();
+        fs::write(dir.join("long"), [b'a'; 257]).unwrap();
+        fs::write(dir.join("exact"), [b'a'; 256]).unwrap();
+
+        let mut provider = SysBusProvider::new(&dir);
+        assert_eq!(provider.read_attr("power_state").unwrap().as_slice(), b"D0");
+        assert_eq!(provider.read_attr("exact").unwrap().len(), 256);
+        assert!(matches!(
+            provider.read_attr("long"),
+            Err(PciBackendError::IOError(err)) if err.kind() == io::ErrorKind::WriteZero
+        ));
+        assert!(provider.read_attr("../power_state").is_err());
+        assert!(provider.read_attr("missing").is_err());
+    }
+
+    #[test]
+    fn drm_cards() {
+        let root = TempDir::new("drm");
+        let devices = root.join("devices");
+        let drm = root.join("drm");
+        for dir in ["0000:03:00.0", "0001:03:00.0", "0000:00:02.0", "soc/gpu"] {
+            fs::create_dir_all(devices.join(dir)).unwrap();
+        }
+        for (card, target) in [
+            ("card1", "0000:03:00.0"),
+            ("card0", "0000:00:02.0"),
+            ("card0-DP-1", "0000:00:02.0"),
+            ("renderD128", "0000:00:02.0"),
+            ("card2", "soc/gpu"),
+            ("card3", "0001:03:00.0"),
+        ] {
+            fs::create_dir_all(drm.join(card)).unwrap();
+            std::os::unix::fs::symlink(devices.join(target), drm.join(card).join("device"))
+                .unwrap();
+        }
+
+        let addresses: Vec<_> = drm_devices_at(&drm)
+            .iter()
+            .map(|device| device.address().to_string())
+            .collect();
+        assert_eq!(addresses, ["0000:00:02.0", "0000:03:00.0", "0001:03:00.0"]);
+        assert!(drm_devices_at(root.join("missing")).is_empty());
+    }
+
+    #[test]
+    fn render_node() {
+        let root = TempDir::new("render");
+        for dir in [
+            "0000:03:00.0/drm/card1",
+            "0000:03:00.0/drm/renderD129",
+            "0000:00:02.0/drm/card0",
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::create_dir_all(root.join("0000:04:00.0")).unwrap();
+
+        let provider = |dir: &str| SysBusProvider::new(root.join(dir));
+        assert_eq!(
+            provider("0000:03:00.0").render_node().unwrap(),
+            Some(PathBuf::from("/dev/dri/renderD129"))
+        );
+        // Display-only, and no DRM driver at all
+        assert_eq!(provider("0000:00:02.0").render_node().unwrap(), None);
+        assert_eq!(provider("0000:04:00.0").render_node().unwrap(), None);
+    }
+
+    #[test]
+    fn connectors() {
+        let root = TempDir::new("connectors");
+        let gpu = root.join("0000:03:00.0/drm/card1");
+        for (connector, status, modes) in [
+            ("card1-HDMI-A-1", "disconnected\n", ""),
+            ("card1-DP-2", "connected\n", "2560x1440\n1920x1080\n"),
+            ("card1-DP-1", "connected\n", "3840x2160\n"),
+            ("card1-DP-3", "connected\n", ""),
+        ] {
+            fs::create_dir_all(gpu.join(connector)).unwrap();
+            fs::write(gpu.join(connector).join("status"), status).unwrap();
+            fs::write(gpu.join(connector).join("modes"), modes).unwrap();
+        }
+        fs::create_dir_all(gpu.join("power")).unwrap();
+        fs::create_dir_all(root.join("0000:03:00.0/drm/renderD129")).unwrap();
+        fs::create_dir_all(root.join("0000:00:02.0/drm/card0")).unwrap();
+        fs::create_dir_all(root.join("0000:04:00.0")).unwrap();
+
+        let provider = |dir: &str| SysBusProvider::new(root.join(dir));
+        let connectors = provider("0000:03:00.0").connectors().unwrap();
+        let shown: Vec<_> = connectors.iter().map(ToString::to_string).collect();
+        assert_eq!(shown, ["DP-1 3840x2160", "DP-2 2560x1440", "DP-3"]);
+        // No monitors, and no DRM driver at all
+        assert_eq!(provider("0000:00:02.0").connectors().unwrap(), []);
+        assert_eq!(provider("0000:04:00.0").connectors().unwrap(), []);
+
+        assert_eq!(parse_preferred_mode(b"1920x1080i\n"), Some((1920, 1080)));
+        assert_eq!(parse_preferred_mode(b""), None);
+    }
+
+    #[test]
+    fn root_override() {
+        use super::super::root_or_default;
+        assert_eq!(root_or_default(None, SYSFS_ROOT), Path::new(SYSFS_ROOT));
+        assert_eq!(
+            root_or_default(Some("".into()), SYSFS_ROOT),
+            Path::new(SYSFS_ROOT)
+        );
+        assert_eq!(
+            root_or_default(Some("/tmp/fixture/devices".into()), SYSFS_ROOT),
+            Path::new("/tmp/fixture/devices")
+        );
+    }
+
+    #[test]
+    fn query_by_address() {
+        let root = TempDir::new("query");
+        fs::create_dir_all(root.join("0000:03:00.0")).unwrap();
+        fs::write(
+            root.join("0000:03:00.0/current_link_speed"),
+            "16.0 GT/s PCIe\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            query_in(&root, "0000:03:00.0", "current_link_speed")
+                .unwrap()
+                .as_slice(),
+            b"16.0 GT/s PCIe"
+        );
+        assert!(matches!(
+            query_in(&root, "03:00.0", "current_link_speed"),
+            Err(PciBackendError::InvalidDevice)
+        ));
+        assert!(matches!(
+            query_in(&root, "0000:04:00.0", "current_link_speed"),
+            Err(PciBackendError::InvalidDevice)
+        ));
+        assert!(matches!(
+            query_in(&root, "0000:03:00.0", "missing"),
+            Err(PciBackendError::IOError(_))
+        ));
+    }
+
+    #[test]
+    fn device_at() {
+        let root = TempDir::new("device-at");
+        fs::create_dir_all(root.join("0000:03:00.0")).unwrap();
+        fs::write(root.join("0000:03:00.0/vendor"), "0x10de\n").unwrap();
+        fs::create_dir_all(root.join("0000:00:1f.3")).unwrap();
+
+        let address = PciDevice::from_address(0, 3, 0, 0).address();
+        let mut direct = SysBusBackend::device_at_in(&root, address).unwrap();
+        assert_eq!(direct.vendor().unwrap(), 0x10de);
+        let mut scanned = SysBusBackend::try_init_at(&root)
+            .unwrap()
+            .at_address(address)
+            .unwrap();
+        assert_eq!(scanned.vendor().unwrap(), 0x10de);
+
+        let missing = PciDevice::from_address(0, 4, 0, 0).address();
+        assert!(matches!(
+            SysBusBackend::device_at_in(&root, missing),
+            Err(PciBackendError::InvalidDevice)
+        ));
+        assert!(matches!(
+            SysBusBackend::try_init_at(&root)
+                .unwrap()
+                .at_address(missing),
+            Err(PciBackendError::InvalidDevice)
+        ));
+    }
+
+    /// Hands out one chunk per `read` call.
+    struct ChunkedReader<'a>(&'a [&'a [u8]]);
+
+    impl Read for ChunkedReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some((chunk, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[..chunk.len()].copy_from_slice(chunk);
+            self.0 = rest;
+            Ok(chunk.len())
+        }
+    }
+
+    #[test]
+    fn short_reads() {
+        let buf: ArrayVec<u8, 16> = read_to_end(ChunkedReader(&[b"0x26", b"84\n"])).unwrap();
+        assert_eq!(parse_u16_hex(&buf), Some(0x2684));
+    }
+}
@@ -0,0 +1,222 @@
+//! An in-memory provider for testing device logic without hardware.
+use super::{DeviceLabel, PciBackendError, PciClass, PciInfoProvider};
+use crate::display::DisplayBytes;
+
+/// Answers every query from fixed values.
+#[derive(Debug, Clone, Copy)]
+pub struct MockProvider {
+    pub vendor: u16,
+    pub device: u16,
+    pub class: PciClass,
+    pub subsystem: Option<(u16, u16)>,
+    pub boot_vga: bool,
+    pub label: Option<&'static str>,
+    pub driver: Option<&'static str>,
+    pub vram: Option<u64>,
+}
+
+impl MockProvider {
+    pub const fn new(vendor: u16, device: u16, class: u8, subclass: u8) -> Self {
+        Self {
+            vendor,
+            device,
+            class: PciClass {
+                class,
+                subclass,
+                prog_if: 0,
+            },
+            subsystem: None,
+            boot_vga: false,
+            label: None,
+            driver: None,
+            vram: None,
+        }
+    }
+}
+
+impl PciInfoProvider for MockProvider {
+    fn get_vendor(&mut self) -> Result<u16, PciBackendError> {
+        Ok(self.vendor)
+    }
+    fn get_device(&mut self) -> Result<u16, PciBackendError> {
+        Ok(self.device)
+    }
+    fn get_class(&mut self) -> Result<PciClass, PciBackendError> {
+        Ok(self.class)
+    }
+    fn get_subsystem_vid(&mut self) -> Result<u16, PciBackendError> {
+        self.subsystem
+            .map(|(vid, _)| vid)
+            .ok_or(PciBackendError::NotAvailable)
+    }
+    fn get_subsystem_did(&mut self) -> Result<u16, PciBackendError> {
+        self.subsystem
+            .map(|(_, did)| did)
+            .ok_or(PciBackendError::NotAvailable)
+    }
+    fn is_boot_vga(&mut self) -> Result<bool, PciBackendError> {
+        Ok(self.boot_vga)
+    }
+    fn label(&mut self) -> Result<Option<DeviceLabel>, PciBackendError> {
+        Ok(self
+            .label
+            .map(|label| DisplayBytes(label.bytes().collect())))
+    }
+    fn driver(&mut self) -> Option<String> {
+        self.driver.map(String::from)
+    }
+    fn vram_bytes(&mut self) -> Option<u64> {
+        self.vram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pci::{primary_of, PciDevIterBackend, PciDevice, PrettyDevice};
+
+    const RTX_4090: MockProvider = MockProvider::new(0x10de, 0x2684, 0x03, 0x00);
+    const HDMI_AUDIO: MockProvider = MockProvider::new(0x10de, 0x22ba, 0x04, 0x03);
+    const RX_7900_XTX: MockProvider = MockProvider::new(0x1002, 0x744c, 0x03, 0x00);
+
+    const UHD_770: MockProvider = MockProvider::new(0x8086, 0xa780, 0x03, 0x00);
+
+    fn device(bus: u8, function: u8, provider: MockProvider) -> PciDevice<MockProvider> {
+        PciDevice::from_address(0, bus, 0, function).with_provider(provider)
+    }
+
+    #[test]
+    fn resolve() {
+        let mut gpu = device(3, 0, RTX_4090);
+        assert!(gpu.is_gpu().unwrap());
+        assert_eq!(gpu.class_name().unwrap(), Some("VGA compatible controller"));
+        let resolved = gpu.resolve().unwrap().unwrap();
+        assert_eq!(
+            PrettyDevice(resolved).to_string(),
+            "NVIDIA GeForce RTX 4090"
+        );
+        assert!(matches!(
+            gpu.subsystem_vendor(),
+            Err(PciBackendError::NotAvailable)
+        ));
+
+        let mut audio = device(3, 1, HDMI_AUDIO);
+        assert!(!audio.is_gpu().unwrap());
+        assert_eq!(audio.class_name().unwrap(), Some("Audio device"));
+    }
+
+    #[test]
+    fn filter_gpus() {
+        let devices = [
+            Ok(device(3, 1, HDMI_AUDIO)),
+            Ok(device(3, 0, RTX_4090)),
+            Err(PciBackendError::InvalidDevice),
+        ];
+        let gpus: Vec<_> = devices
+            .into_iter()
+            .filter_class(PciClass::DISPLAY)
+            .flatten()
+            .map(|gpu| gpu.address().function)
+            .collect();
+        assert_eq!(gpus, [0]);
+    }
+
+    #[test]
+    fn filter_vendor() {
+        let devices = [
+            Ok(device(3, 0, RTX_4090)),
+            Ok(device(3, 1, HDMI_AUDIO)),
+            Ok(device(4, 0, RX_7900_XTX)),
+            Err(PciBackendError::AccessDenied),
+        ];
+        let nvidia_gpus: Vec<_> = devices
+            .into_iter()
+            .filter_class(PciClass::DISPLAY)
+            .filter_vendor(0x10de)
+            .map(|gpu| gpu.map(|gpu| gpu.address().slot()))
+            .collect();
+        assert!(matches!(
+            nvidia_gpus[..],
+            [Ok((0, 3, 0)), Err(PciBackendError::AccessDenied)]
+        ));
+    }
+
+    #[test]
+    fn primary_selection() {
+        let primary = |gpus: Vec<PciDevice<MockProvider>>| {
+            primary_of(gpus.into_iter()).map(|gpu| (gpu.address().bus, gpu.address().function))
+        };
+        // The boot display wins, even when it's the iGPU and comes last
+        let mut boot_igpu = UHD_770;
+        boot_igpu.boot_vga = true;
+        assert_eq!(
+            primary(vec![
+                device(3, 0, RTX_4090),
+                device(4, 0, RX_7900_XTX),
+                device(0, 0, boot_igpu),
+            ]),
+            Some((0, 0))
+        );
+        // Without one, the first discrete GPU
+        assert_eq!(
+            primary(vec![
+                device(0, 0, UHD_770),
+                device(4, 0, RX_7900_XTX),
+                device(3, 0, RTX_4090),
+            ]),
+            Some((4, 0))
+        );
+        // Then the first GPU at all
+        assert_eq!(
+            primary(vec![device(0, 1, UHD_770), device(0, 0, UHD_770)]),
+            Some((0, 1))
+        );
+        assert_eq!(primary(Vec::new()), None);
+    }
+
+    #[test]
+    fn primary_across_domains() {
+        let integrated = device(0, 0, UHD_770);
+        let passthrough = PciDevice::from_address(1, 0, 0, 0).with_provider(RTX_4090);
+        let primary = primary_of([integrated, passthrough].into_iter()).unwrap();
+        assert_eq!(primary.address().slot(), (1, 0, 0));
+
+        // The same bus/device/function in two domains are two devices
+        let mut boot = RX_7900_XTX;
+        boot.boot_vga = true;
+        let gpus = [
+            PciDevice::from_address(0, 3, 0, 0).with_provider(RTX_4090),
+            PciDevice::from_address(1, 3, 0, 0).with_provider(boot),
+        ];
+        let mut primary = primary_of(gpus.into_iter()).unwrap();
+        assert_eq!(primary.address().domain, 1);
+        assert_eq!(primary.vendor().unwrap(), 0x1002);
+    }
+
+    #[test]
+    fn lenient_union() {
+        // A GPU whose class byte claims it's an unclassified device, bound by a DRM driver
+        let mislabeled = MockProvider::new(0x1002, 0x744c, 0x00, 0x00);
+        let by_class = vec![device(3, 0, RTX_4090), device(0, 0, UHD_770)];
+        let drm = vec![device(4, 0, mislabeled), device(3, 0, RTX_4090)];
+        let gpus = crate::pci::union_by_address(by_class, drm);
+        let found: Vec<_> = gpus.iter().map(|gpu| gpu.address().slot()).collect();
+        assert_eq!(found, [(0, 0, 0), (0, 3, 0), (0, 4, 0)]);
+        assert!(!gpus.into_iter().last().unwrap().is_gpu().unwrap());
+    }
+
+    #[test]
+    fn count_ok() {
+        let devices = || {
+            [
+                Ok(device(3, 0, RTX_4090)),
+                Err(PciBackendError::AccessDenied),
+                Ok(device(3, 1, HDMI_AUDIO)),
+            ]
+            .into_iter()
+        };
+        assert_eq!(devices().count(), 3);
+        assert_eq!(devices().count_ok(), 2);
+        assert_eq!(devices().devices_only().count(), 2);
+    }
+}
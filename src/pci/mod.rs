@@ -0,0 +1,803 @@
+//! PCI device enumeration.
+//!
+//! Backends iterate over the devices present on the system, yielding [`PciDevice`]s whose
+//! [`PciInfoProvider`] reads the device's IDs on demand.
+//!
+//! For testing against a captured tree, the Linux backends look for devices under
+//! `$RXFETCH_SYSFS_ROOT`, `$RXFETCH_PROCFS_ROOT` and `$RXFETCH_DRM_ROOT` when set, instead of
+//! `/sys/bus/pci/devices`, `/proc/bus/pci` and `/sys/class/drm`.
+pub mod config_space;
+pub mod gpu;
+pub mod group;
+pub mod ids;
+#[cfg(target_os = "linux")]
+pub mod linux_procfs;
+#[cfg(target_os = "linux")]
+pub mod linux_sysfs;
+#[cfg(test)]
+pub(crate) mod mock;
+mod pretty;
+#[cfg(all(target_os = "linux", feature = "tokio"))]
+pub mod stream;
+
+pub use config_space::{parse_config_space, CommandFlags, DeviceInfo};
+pub use pretty::{FullDevice, PrettyDevice, PrettyName};
+
+use crate::{arrayvec::ArrayVec, display::DisplayBytes};
+use pci_ids::FromId;
+use std::{borrow::BorrowMut, fmt, io, path::PathBuf};
+
+/// The directory a backend enumerates: the value of the environment variable `var`
+/// if it's set and not empty, `default` otherwise.
+#[cfg(target_os = "linux")]
+fn backend_root(var: &str, default: &str) -> PathBuf {
+    root_or_default(std::env::var_os(var), default)
+}
+
+/// `root` if it's set and not empty, `default` otherwise, see [`backend_root`].
+#[cfg(target_os = "linux")]
+fn root_or_default(root: Option<std::ffi::OsString>, default: &str) -> PathBuf {
+    root.filter(|root| !root.is_empty())
+        .map_or_else(|| default.into(), PathBuf::from)
+}
+
+/// A device label read from the system, see [`PciDevice::label`].
+pub type DeviceLabel = DisplayBytes<ArrayVec<u8, 256>>;
+
+#[derive(Debug)]
+pub enum PciBackendError {
+    /// The backend or the requested information isn't available on this system.
+    NotAvailable,
+    /// The device couldn't be parsed or no longer exists.
+    InvalidDevice,
+    /// Reading the information requires more privileges, e.g. parts of the config space need root.
+    AccessDenied,
+    IOError(io::Error),
+}
+
+impl From<io::Error> for PciBackendError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::PermissionDenied => PciBackendError::AccessDenied,
+            _ => PciBackendError::IOError(err),
+        }
+    }
+}
+
+impl fmt::Display for PciBackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PciBackendError::NotAvailable => f.write_str("not available"),
+            PciBackendError::InvalidDevice => f.write_str("invalid device"),
+            PciBackendError::AccessDenied => f.write_str("permission denied"),
+            PciBackendError::IOError(err) => write!(f, "IO error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PciBackendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PciBackendError::IOError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// The location of a device on the PCI bus, displayed as `dddd:bb:dd.f`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PciAddress {
+    pub domain: u16,
+    pub bus: u8,
+    pub device: u8,
+    pub function: u8,
+}
+
+impl PciAddress {
+    /// The `(domain, bus, device)` triple, identifying the physical device this function belongs to.
+    pub const fn slot(&self) -> (u16, u8, u8) {
+        (self.domain, self.bus, self.device)
+    }
+    /// Whether the device sits directly on the root bus of the first domain,
+    /// which is where integrated GPUs are found, while discrete ones sit behind a bridge.
+    ///
+    /// Devices in other domains are never integrated, e.g. GPUs passed through by Hyper-V get
+    /// a domain of their own with the device on bus 0. Some AMD APUs do sit behind an internal
+    /// bridge though, so this is only a heuristic.
+    pub const fn is_first_root_bus(&self) -> bool {
+        self.domain == 0 && self.bus == 0
+    }
+}
+
+impl fmt::Display for PciAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04x}:{:02x}:{:02x}.{:x}",
+            self.domain, self.bus, self.device, self.function
+        )
+    }
+}
+
+/// The class code of a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PciClass {
+    pub class: u8,
+    pub subclass: u8,
+    pub prog_if: u8,
+}
+
+impl PciClass {
+    /// Base class of display controllers.
+    pub const DISPLAY: u8 = 0x03;
+
+    /// The most specific name the PCI ID database has for this class.
+    pub fn name(&self) -> Option<&'static str> {
+        pci_ids::Subclass::from_cid_sid(self.class, self.subclass)
+            .map(pci_ids::Subclass::name)
+            .or_else(|| pci_ids::Class::from_id(self.class).map(pci_ids::Class::name))
+    }
+}
+
+/// The PCIe generation with the given per-lane transfer rate in GT/s, e.g. 4 for 16 GT/s.
+pub fn pcie_generation(gts: f32) -> Option<u8> {
+    const RATES: [f32; 6] = [2.5, 5.0, 8.0, 16.0, 32.0, 64.0];
+    let idx = RATES.iter().position(|&rate| (rate - gts).abs() < 0.1)?;
+    Some(idx as u8 + 1)
+}
+
+/// Reads identifying information about a single device.
+pub trait PciInfoProvider {
+    fn get_vendor(&mut self) -> Result<u16, PciBackendError>;
+    fn get_device(&mut self) -> Result<u16, PciBackendError>;
+    fn get_class(&mut self) -> Result<PciClass, PciBackendError>;
+    fn get_subsystem_vid(&mut self) -> Result<u16, PciBackendError>;
+    fn get_subsystem_did(&mut self) -> Result<u16, PciBackendError>;
+    /// Whether the firmware used this device as the boot display.
+    fn is_boot_vga(&mut self) -> Result<bool, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+    /// The raw command register, at offset `0x04` of the config space.
+    fn command_register(&mut self) -> Result<u16, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+    /// The negotiated PCIe link as `(GT/s, lanes)`, or `None` for non-PCIe devices.
+    fn link_status(&mut self) -> Result<Option<(f32, u8)>, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+    /// The NUMA node the device is attached to, or `None` if it has no NUMA affinity.
+    fn numa_node(&mut self) -> Result<Option<i32>, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+    /// The temperature in °C reported by the device's driver, if it exposes one.
+    fn temperature(&mut self) -> Option<f32> {
+        None
+    }
+    /// How busy the device is, in percent, if its driver exposes it.
+    fn utilization(&mut self) -> Option<u8> {
+        None
+    }
+    /// The power drawn in watts, or `None` if the driver doesn't expose it.
+    fn power_draw(&mut self) -> Result<Option<f32>, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+    /// The current core clock in MHz, if the driver exposes it.
+    fn core_clock_mhz(&mut self) -> Option<u32> {
+        None
+    }
+    /// The current memory clock in MHz, if the driver exposes it.
+    fn mem_clock_mhz(&mut self) -> Option<u32> {
+        None
+    }
+    /// The DRM render node, e.g. `/dev/dri/renderD128`, or `None` if the device has none.
+    fn render_node(&mut self) -> Result<Option<PathBuf>, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+    /// The display connectors with a monitor attached, empty if there are none.
+    fn connectors(&mut self) -> Result<Vec<gpu::ConnectorInfo>, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+    /// The label the firmware gives the device, e.g. an OEM product name, or `None` if it has none.
+    fn label(&mut self) -> Result<Option<DeviceLabel>, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+    /// The name of the kernel driver bound to the device, e.g. `amdgpu`.
+    fn driver(&mut self) -> Option<String> {
+        None
+    }
+    /// The dedicated video memory in bytes, if the driver exposes it.
+    fn vram_bytes(&mut self) -> Option<u64> {
+        None
+    }
+}
+
+/// A provider for devices that only carry an address, every query returns [`PciBackendError::NotAvailable`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProvider;
+
+impl PciInfoProvider for NoProvider {
+    fn get_vendor(&mut self) -> Result<u16, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+    fn get_device(&mut self) -> Result<u16, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+    fn get_class(&mut self) -> Result<PciClass, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+    fn get_subsystem_vid(&mut self) -> Result<u16, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+    fn get_subsystem_did(&mut self) -> Result<u16, PciBackendError> {
+        Err(PciBackendError::NotAvailable)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PciDevice<P = NoProvider> {
+    address: PciAddress,
+    provider: P,
+}
+
+impl PciDevice {
+    pub(crate) const fn new(address: PciAddress) -> Self {
+        Self {
+            address,
+            provider: NoProvider,
+        }
+    }
+    /// A device at a known address, without any way to read its information.
+    ///
+    /// Attach a provider with [`PciDevice::with_provider`], or use [`PciDevIterBackend::at_address`]
+    /// to look the device up through a backend.
+    pub const fn from_address(domain: u16, bus: u8, device: u8, function: u8) -> Self {
+        Self::new(PciAddress {
+            domain,
+            bus,
+            device,
+            function,
+        })
+    }
+}
+
+impl<P> PciDevice<P> {
+    pub fn with_provider<Q>(self, provider: Q) -> PciDevice<Q> {
+        PciDevice {
+            address: self.address,
+            provider,
+        }
+    }
+    // Only the Linux backends wrap their providers
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn map_provider<Q>(self, f: impl FnOnce(P) -> Q) -> PciDevice<Q> {
+        PciDevice {
+            address: self.address,
+            provider: f(self.provider),
+        }
+    }
+    pub const fn address(&self) -> PciAddress {
+        self.address
+    }
+    pub fn provider(&mut self) -> &mut P {
+        &mut self.provider
+    }
+}
+
+impl<P: PciInfoProvider> PciDevice<P> {
+    pub fn vendor(&mut self) -> Result<u16, PciBackendError> {
+        self.provider.get_vendor()
+    }
+    pub fn device(&mut self) -> Result<u16, PciBackendError> {
+        self.provider.get_device()
+    }
+    pub fn class(&mut self) -> Result<PciClass, PciBackendError> {
+        self.provider.get_class()
+    }
+    pub fn subsystem_vendor(&mut self) -> Result<u16, PciBackendError> {
+        self.provider.get_subsystem_vid()
+    }
+    pub fn subsystem_device(&mut self) -> Result<u16, PciBackendError> {
+        self.provider.get_subsystem_did()
+    }
+    /// Whether the firmware used this device as the boot display.
+    pub fn is_boot_vga(&mut self) -> Result<bool, PciBackendError> {
+        self.provider.is_boot_vga()
+    }
+    /// The decoded command register, e.g. whether the device may access memory or do DMA.
+    pub fn command(&mut self) -> Result<CommandFlags, PciBackendError> {
+        self.provider.command_register().map(CommandFlags)
+    }
+    /// The negotiated PCIe link as `(GT/s, lanes)`, or `None` for non-PCIe devices.
+    pub fn link_status(&mut self) -> Result<Option<(f32, u8)>, PciBackendError> {
+        self.provider.link_status()
+    }
+    /// The NUMA node the device is attached to, or `None` if it has no NUMA affinity.
+    pub fn numa_node(&mut self) -> Result<Option<i32>, PciBackendError> {
+        self.provider.numa_node()
+    }
+    /// The temperature in °C, currently only exposed by drivers with hwmon support such as `amdgpu`.
+    pub fn temperature(&mut self) -> Option<f32> {
+        self.provider.temperature()
+    }
+    /// The utilization in percent, currently only exposed by `amdgpu`.
+    pub fn utilization(&mut self) -> Option<u8> {
+        self.provider.utilization()
+    }
+    /// The power drawn in watts, averaged by the driver. Currently only exposed by `amdgpu`.
+    pub fn power_draw(&mut self) -> Result<Option<f32>, PciBackendError> {
+        self.provider.power_draw()
+    }
+    /// The current core clock in MHz, currently only exposed by `amdgpu`.
+    pub fn core_clock_mhz(&mut self) -> Option<u32> {
+        self.provider.core_clock_mhz()
+    }
+    /// The current memory clock in MHz, currently only exposed by `amdgpu`.
+    pub fn mem_clock_mhz(&mut self) -> Option<u32> {
+        self.provider.mem_clock_mhz()
+    }
+    /// The DRM render node to open the GPU through, e.g. for Vulkan or EGL.
+    ///
+    /// `None` for devices without one, such as display-only GPUs or ones without a DRM driver.
+    pub fn render_node(&mut self) -> Result<Option<PathBuf>, PciBackendError> {
+        self.provider.render_node()
+    }
+    /// The connectors of the GPU that have a monitor attached, e.g. to tell which card drives
+    /// which monitor.
+    pub fn connectors(&mut self) -> Result<Vec<gpu::ConnectorInfo>, PciBackendError> {
+        self.provider.connectors()
+    }
+    /// The firmware's label for the device, which [`PrettyName`] prefers over the database name.
+    ///
+    /// Most devices have none, only those the ACPI tables describe do.
+    pub fn label(&mut self) -> Result<Option<DeviceLabel>, PciBackendError> {
+        self.provider.label()
+    }
+    /// The bound kernel driver, `None` if there's none or the backend can't tell.
+    pub fn driver(&mut self) -> Option<String> {
+        self.provider.driver()
+    }
+    /// The video memory in bytes, currently only exposed by `amdgpu`.
+    pub fn vram_bytes(&mut self) -> Option<u64> {
+        self.provider.vram_bytes()
+    }
+    /// Whether this device is a display controller.
+    pub fn is_gpu(&mut self) -> Result<bool, PciBackendError> {
+        Ok(self.class()?.class == PciClass::DISPLAY)
+    }
+    pub fn class_name(&mut self) -> Result<Option<&'static str>, PciBackendError> {
+        Ok(self.class()?.name())
+    }
+    /// Looks the device up in the PCI ID database.
+    pub fn resolve(&mut self) -> Result<Option<&'static pci_ids::Device>, PciBackendError> {
+        Ok(pci_ids::Device::from_vid_pid(
+            self.vendor()?,
+            self.device()?,
+        ))
+    }
+    /// Reads all identifying information and looks it up in the PCI ID database.
+    pub fn resolved(&mut self) -> Result<ResolvedDevice, PciBackendError> {
+        let vendor_id = self.vendor()?;
+        let device_id = self.device()?;
+        Ok(ResolvedDevice {
+            address: self.address,
+            vendor_id,
+            device_id,
+            class: self.class()?,
+            device: pci_ids::Device::from_vid_pid(vendor_id, device_id),
+        })
+    }
+}
+
+/// A device with its IDs looked up in the PCI ID database.
+///
+/// Displays as an `lspci`-style line: address, class and name.
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedDevice {
+    pub address: PciAddress,
+    pub vendor_id: u16,
+    pub device_id: u16,
+    pub class: PciClass,
+    /// `None` if the device isn't in the database.
+    pub device: Option<&'static pci_ids::Device>,
+}
+
+impl ResolvedDevice {
+    pub fn class_name(&self) -> Option<&'static str> {
+        self.class.name()
+    }
+    pub fn vendor_name(&self) -> Option<&'static str> {
+        match self.device {
+            Some(device) => Some(device.vendor().name()),
+            None => pci_ids::Vendor::from_id(self.vendor_id).map(pci_ids::Vendor::name),
+        }
+    }
+    pub fn device_name(&self) -> Option<&'static str> {
+        self.device.map(pci_ids::Device::name)
+    }
+}
+
+impl ResolvedDevice {
+    /// Writes the `lspci`-style line, with the vendor and device names looked up by the caller.
+    fn write_line(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        vendor_name: Option<&str>,
+        device_name: Option<&str>,
+    ) -> fmt::Result {
+        write!(f, "{} ", self.address)?;
+        match self.class_name() {
+            Some(name) => write!(f, "{name}: ")?,
+            None => write!(
+                f,
+                "Class {:02x}{:02x}: ",
+                self.class.class, self.class.subclass
+            )?,
+        }
+        match vendor_name {
+            Some(name) => write!(f, "{name} ")?,
+            None => write!(f, "Vendor {:04x} ", self.vendor_id)?,
+        }
+        match device_name {
+            Some(name) => f.write_str(name),
+            None => write!(f, "Device {:04x}", self.device_id),
+        }
+    }
+}
+
+impl fmt::Display for ResolvedDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_line(f, self.vendor_name(), self.device_name())
+    }
+}
+
+/// An iterator over the PCI devices of a backend.
+///
+/// Implemented for every iterator yielding `Result<PciDevice<_>, PciBackendError>`.
+/// Devices that fail to read are yielded as errors, so [`Iterator::count`] includes them,
+/// use [`PciDevIterBackend::count_ok`] to count only the devices that were read.
+pub trait PciDevIterBackend:
+    Iterator<Item = Result<PciDevice<Self::Provider>, PciBackendError>> + Sized
+{
+    type Provider: PciInfoProvider;
+
+    /// Yields only devices with the given base class, e.g. [`PciClass::DISPLAY`].
+    fn filter_class(self, class: u8) -> ClassFilter<Self> {
+        ClassFilter { iter: self, class }
+    }
+    /// Yields only devices from the given vendor, e.g. `0x10de` for NVIDIA.
+    ///
+    /// This reads the vendor of every device, while [`PciDevIterBackend::filter_class`] only
+    /// reads the class, so apply the class filter first when combining them.
+    /// Devices whose vendor fails to read are yielded as errors.
+    fn filter_vendor(self, vendor: u16) -> VendorFilter<Self> {
+        VendorFilter { iter: self, vendor }
+    }
+    /// Skips devices that failed to read.
+    fn devices_only(self) -> std::iter::Flatten<Self> {
+        self.flatten()
+    }
+    /// The number of devices that were read successfully.
+    fn count_ok(self) -> usize {
+        self.devices_only().count()
+    }
+    /// Resolves every device against the PCI ID database.
+    fn resolved(self) -> Resolved<Self> {
+        Resolved { iter: self }
+    }
+    /// Groups the functions of each physical device, see [`group::group_by_slot`].
+    ///
+    /// Devices that fail to read are skipped.
+    fn slot_groups(self) -> Vec<group::SlotGroup> {
+        group::group_by_slot(self.resolved().flatten())
+    }
+    /// Finds the device at `address`, returning [`PciBackendError::InvalidDevice`] if there's none.
+    ///
+    /// This enumerates devices until it gets there,
+    /// [`linux_sysfs::SysBusBackend::device_at`] opens the device directly.
+    fn at_address(self, address: PciAddress) -> Result<PciDevice<Self::Provider>, PciBackendError> {
+        self.flatten()
+            .find(|device| device.address() == address)
+            .ok_or(PciBackendError::InvalidDevice)
+    }
+}
+
+impl<P, I> PciDevIterBackend for I
+where
+    P: PciInfoProvider,
+    I: Iterator<Item = Result<PciDevice<P>, PciBackendError>>,
+{
+    type Provider = P;
+}
+
+/// Created by [`PciDevIterBackend::filter_class`].
+pub struct ClassFilter<I> {
+    iter: I,
+    class: u8,
+}
+
+impl<I: PciDevIterBackend> Iterator for ClassFilter<I> {
+    type Item = I::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut device = match self.iter.next()? {
+                Ok(device) => device,
+                err => return Some(err),
+            };
+            match device.class() {
+                Ok(class) if class.class == self.class => return Some(Ok(device)),
+                Ok(_) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// Created by [`PciDevIterBackend::filter_vendor`].
+pub struct VendorFilter<I> {
+    iter: I,
+    vendor: u16,
+}
+
+impl<I: PciDevIterBackend> Iterator for VendorFilter<I> {
+    type Item = I::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut device = match self.iter.next()? {
+                Ok(device) => device,
+                err => return Some(err),
+            };
+            match device.vendor() {
+                Ok(vendor) if vendor == self.vendor => return Some(Ok(device)),
+                Ok(_) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// The GPUs in the system, found through `/sys/class/drm` without enumerating the whole bus.
+///
+/// Falls back to filtering [`PciAutoIter`] when there are no DRM cards,
+/// e.g. when no GPU driver is loaded. Devices that fail to read are skipped.
+pub fn gpus_fast() -> Result<Vec<PciDevice<AutoProvider>>, PciBackendError> {
+    #[cfg(target_os = "linux")]
+    {
+        let gpus = linux_sysfs::drm_devices();
+        if !gpus.is_empty() {
+            return Ok(gpus
+                .into_iter()
+                .map(|gpu| gpu.map_provider(AutoProvider::SysBus))
+                .collect());
+        }
+    }
+    Ok(PciAutoIter::try_init()?
+        .filter_class(PciClass::DISPLAY)
+        .flatten()
+        .collect())
+}
+
+/// Created by [`PciDevIterBackend::resolved`].
+pub struct Resolved<I> {
+    iter: I,
+}
+
+impl<I: PciDevIterBackend> Iterator for Resolved<I> {
+    type Item = Result<ResolvedDevice, PciBackendError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.iter.next()?.and_then(|mut device| device.resolved()))
+    }
+}
+
+/// Uses the best backend available on the system.
+pub enum PciAutoIter {
+    #[cfg(target_os = "linux")]
+    SysBus(linux_sysfs::SysBusBackend),
+    #[cfg(target_os = "linux")]
+    ProcBus(linux_procfs::ProcBusBackend),
+    /// Stands in on platforms without a backend, so code using [`PciAutoIter`] still builds.
+    /// Always yields nothing.
+    #[cfg(not(target_os = "linux"))]
+    Unsupported,
+}
+
+impl PciAutoIter {
+    /// Tries sysfs first, falling back to procfs.
+    ///
+    /// On platforms without a backend this succeeds with [`PciAutoIter::Unsupported`],
+    /// which yields no devices.
+    pub fn try_init() -> Result<Self, PciBackendError> {
+        #[cfg(target_os = "linux")]
+        return linux_sysfs::SysBusBackend::try_init()
+            .map(PciAutoIter::SysBus)
+            .or_else(|_| linux_procfs::ProcBusBackend::try_init().map(PciAutoIter::ProcBus));
+        #[cfg(not(target_os = "linux"))]
+        Ok(PciAutoIter::Unsupported)
+    }
+    /// Like [`PciAutoIter::try_init`], enumerating `sysfs` and `procfs` instead of
+    /// `/sys/bus/pci/devices` and `/proc/bus/pci`.
+    ///
+    /// Fails with [`PciBackendError::NotAvailable`] when neither exists, e.g. in a container
+    /// that mounts neither.
+    #[cfg(target_os = "linux")]
+    pub fn try_init_in(
+        sysfs: impl Into<PathBuf>,
+        procfs: impl Into<PathBuf>,
+    ) -> Result<Self, PciBackendError> {
+        linux_sysfs::SysBusBackend::try_init_at(sysfs)
+            .map(PciAutoIter::SysBus)
+            .or_else(|_| {
+                linux_procfs::ProcBusBackend::try_init_at(procfs).map(PciAutoIter::ProcBus)
+            })
+    }
+    /// Picks the GPU driving the display: the boot VGA device, falling back to the first
+    /// discrete GPU and then to the first GPU found.
+    ///
+    /// Devices that fail to read are skipped.
+    pub fn primary_gpu(self) -> Option<PciDevice<AutoProvider>> {
+        primary_of(self.filter_class(PciClass::DISPLAY).flatten())
+    }
+    /// Like filtering for [`PciClass::DISPLAY`], but also includes the devices a DRM driver
+    /// created a `/sys/class/drm/card*` node for, whatever their class.
+    ///
+    /// This catches the occasional GPU with a mislabeled class byte. The risk is the reverse:
+    /// anything else a DRM driver binds to, such as a display controller on an add-in card or
+    /// a virtual GPU exposing a different class, shows up as a GPU too.
+    /// Prefer the class-based path unless GPUs are known to go missing.
+    ///
+    /// Ordered by address. Devices that fail to read are skipped.
+    pub fn gpus_lenient(self) -> Vec<PciDevice<AutoProvider>> {
+        let gpus = self.filter_class(PciClass::DISPLAY).flatten().collect();
+        #[cfg(target_os = "linux")]
+        let drm = linux_sysfs::drm_devices()
+            .into_iter()
+            .map(|gpu| gpu.map_provider(AutoProvider::SysBus))
+            .collect();
+        #[cfg(not(target_os = "linux"))]
+        let drm = Vec::new();
+        union_by_address(gpus, drm)
+    }
+    /// Every GPU, ordered by VRAM, largest first.
+    ///
+    /// GPUs whose driver doesn't expose their VRAM come last. Ties put discrete GPUs first,
+    /// then the boot display, then go by address. GPUs that fail to read are skipped.
+    pub fn gpus_by_vram(self) -> Vec<gpu::GpuSummary> {
+        gpu::by_vram(self.filter_class(PciClass::DISPLAY).flatten())
+            .into_iter()
+            .filter_map(gpu::summary)
+            .collect()
+    }
+}
+
+/// Both sets of devices ordered by address, keeping the first of each address.
+fn union_by_address<P>(mut a: Vec<PciDevice<P>>, b: Vec<PciDevice<P>>) -> Vec<PciDevice<P>> {
+    a.extend(b);
+    // Stable, so devices from `a` stay ahead of their duplicates
+    a.sort_by_key(PciDevice::address);
+    a.dedup_by_key(|device| device.address());
+    a
+}
+
+/// The selection behind [`PciAutoIter::primary_gpu`], for any provider.
+///
+/// Takes owned devices or mutable references to them.
+pub(crate) fn primary_of<P: PciInfoProvider, D: BorrowMut<PciDevice<P>>>(
+    gpus: impl Iterator<Item = D>,
+) -> Option<D> {
+    let mut discrete = None;
+    let mut first = None;
+    for mut gpu in gpus {
+        if gpu.borrow_mut().is_boot_vga().unwrap_or(false) {
+            return Some(gpu);
+        }
+        if discrete.is_none() && !gpu.borrow_mut().address().is_first_root_bus() {
+            discrete = Some(gpu);
+        } else if first.is_none() {
+            first = Some(gpu);
+        }
+    }
+    discrete.or(first)
+}
+
+impl Iterator for PciAutoIter {
+    type Item = Result<PciDevice<AutoProvider>, PciBackendError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            #[cfg(target_os = "linux")]
+            PciAutoIter::SysBus(iter) => Some(
+                iter.next()?
+                    .map(|dev| dev.map_provider(AutoProvider::SysBus)),
+            ),
+            #[cfg(target_os = "linux")]
+            PciAutoIter::ProcBus(iter) => Some(
+                iter.next()?
+                    .map(|dev| dev.map_provider(AutoProvider::ProcBus)),
+            ),
+            #[cfg(not(target_os = "linux"))]
+            PciAutoIter::Unsupported => None,
+        }
+    }
+}
+
+/// The provider of devices yielded by [`PciAutoIter`].
+pub enum AutoProvider {
+    #[cfg(target_os = "linux")]
+    SysBus(linux_sysfs::SysBusProvider),
+    #[cfg(target_os = "linux")]
+    ProcBus(linux_procfs::ProcBusProvider),
+    /// Never constructed, as [`PciAutoIter::Unsupported`] yields no devices.
+    #[cfg(not(target_os = "linux"))]
+    Unsupported(NoProvider),
+}
+
+macro_rules! delegate {
+    ($self:ident.$method:ident()) => {
+        match $self {
+            #[cfg(target_os = "linux")]
+            AutoProvider::SysBus(provider) => provider.$method(),
+            #[cfg(target_os = "linux")]
+            AutoProvider::ProcBus(provider) => provider.$method(),
+            #[cfg(not(target_os = "linux"))]
+            AutoProvider::Unsupported(provider) => provider.$method(),
+        }
+    };
+}
+
+impl PciInfoProvider for AutoProvider {
+    fn get_vendor(&mut self) -> Result<u16, PciBackendError> {
+        delegate!(self.get_vendor())
+    }
+    fn get_device(&mut self) -> Result<u16, PciBackendError> {
+        delegate!(self.get_device())
+    }
+    fn get_class(&mut self) -> Result<PciClass, PciBackendError> {
+        delegate!(self.get_class())
+    }
+    fn get_subsystem_vid(&mut self) -> Result<u16, PciBackendError> {
+        delegate!(self.get_subsystem_vid())
+    }
+    fn get_subsystem_did(&mut self) -> Result<u16, PciBackendError> {
+        delegate!(self.get_subsystem_did())
+    }
+    fn is_boot_vga(&mut self) -> Result<bool, PciBackendError> {
+        delegate!(self.is_boot_vga())
+    }
+    fn command_register(&mut self) -> Result<u16, PciBackendError> {
+        delegate!(self.command_register())
+    }
+    fn link_status(&mut self) -> Result<Option<(f32, u8)>, PciBackendError> {
+        delegate!(self.link_status())
+    }
+    fn numa_node(&mut self) -> Result<Option<i32>, PciBackendError> {
+        delegate!(self.numa_node())
+    }
+    fn temperature(&mut self) -> Option<f32> {
+        delegate!(self.temperature())
+    }
+    fn utilization(&mut self) -> Option<u8> {
+        delegate!(self.utilization())
+    }
+    fn power_draw(&mut self) -> Result<Option<f32>, PciBackendError> {
+        delegate!(self.power_draw())
+    }
+    fn core_clock_mhz(&mut self) -> Option<u32> {
+        delegate!(self.core_clock_mhz())
+    }
+    fn mem_clock_mhz(&mut self) -> Option<u32> {
+        delegate!(self.mem_clock_mhz())
+    }
+    fn render_node(&mut self) -> Result<Option<PathBuf>, PciBackendError> {
+        delegate!(self.render_node())
+    }
+    fn connectors(&mut self) -> Result<Vec<gpu::ConnectorInfo>, PciBackendError> {
+        delegate!(self.connectors())
+    }
+    fn label(&mut self) -> Result<Option<DeviceLabel>, PciBackendError> {
+        delegate!(self.label())
+    }
+    fn driver(&mut self) -> Option<String> {
+        delegate!(self.driver())
+    }
+    fn vram_bytes(&mut self) -> Option<u64> {
+        delegate!(self.vram_bytes())
+    }
+}
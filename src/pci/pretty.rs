@@ -0,0 +1,157 @@
+use super::{ids::PciIdDb, ResolvedDevice};
+use std::fmt;
+
+/// Displays a shortened `vendor device` name suitable for a fetch line,
+/// e.g. `NVIDIA GeForce RTX 4090` rather than `NVIDIA Corporation AD102 [GeForce RTX 4090]`.
+#[derive(Clone, Copy)]
+pub struct PrettyDevice<'d>(pub &'d pci_ids::Device);
+
+/// The contents of the last `[...]` group in `name`, if any.
+fn bracketed(name: &str) -> Option<&str> {
+    let start = name.rfind('[')? + 1;
+    let end = start + name[start..].find(']')?;
+    Some(&name[start..end])
+}
+
+fn short_vendor(name: &str) -> &str {
+    bracketed(name)
+        .or_else(|| name.split_whitespace().next())
+        .unwrap_or(name)
+}
+
+fn short_device(name: &str) -> &str {
+    let name = bracketed(name).unwrap_or(name);
+    name.strip_suffix(" Laptop GPU").unwrap_or(name).trim()
+}
+
+impl fmt::Display for PrettyDevice<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            short_vendor(self.0.vendor().name()),
+            short_device(self.0.name())
+        )
+    }
+}
+
+/// Displays the `vendor device` name exactly as the PCI ID database has it,
+/// e.g. `NVIDIA Corporation AD102 [GeForce RTX 4090]`.
+#[derive(Clone, Copy)]
+pub struct FullDevice<'d>(pub &'d pci_ids::Device);
+
+impl fmt::Display for FullDevice<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            self.0.vendor().name().trim(),
+            self.0.name().trim()
+        )
+    }
+}
+
+/// The name to show for a device: its firmware label if it has one, then the name from `db`
+/// shortened like [`PrettyDevice`] does, then the raw `vendor:device` IDs.
+///
+/// An empty [`PciIdDb`] only has the names compiled into `pci-ids`.
+///
+/// Labels are more accurate for OEM and rebranded cards, of which the PCI ID database
+/// only knows the reference design.
+#[derive(Clone, Copy)]
+pub struct PrettyName<'d> {
+    pub device: &'d ResolvedDevice,
+    pub label: Option<&'d [u8]>,
+    pub db: &'d PciIdDb,
+}
+
+impl fmt::Display for PrettyName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(label) = self.label.filter(|label| !label.is_empty()) {
+            return write!(f, "{}", crate::display::DisplayBytes(label));
+        }
+        let (vendor_id, device_id) = (self.device.vendor_id, self.device.device_id);
+        match (
+            self.db.vendor_name(vendor_id),
+            self.db.device_name(vendor_id, device_id),
+        ) {
+            (Some(vendor), Some(device)) => {
+                write!(f, "{} {}", short_vendor(vendor), short_device(device))
+            }
+            _ => write!(
+                f,
+                "{:04x}:{:04x}",
+                self.device.vendor_id, self.device.device_id
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortening() {
+        assert_eq!(short_vendor("NVIDIA Corporation"), "NVIDIA");
+        assert_eq!(
+            short_vendor("Advanced Micro Devices, Inc. [AMD/ATI]"),
+            "AMD/ATI"
+        );
+        assert_eq!(
+            short_device("AD106M [GeForce RTX 4070 Max-Q / Mobile]"),
+            "GeForce RTX 4070 Max-Q / Mobile"
+        );
+        assert_eq!(
+            short_device("GA107M [GeForce RTX 3050 Laptop GPU]"),
+            "GeForce RTX 3050"
+        );
+        assert_eq!(short_device("Alder Lake-P GT2"), "Alder Lake-P GT2");
+    }
+
+    #[test]
+    fn full_name() {
+        let device = pci_ids::Device::from_vid_pid(0x10de, 0x2684).unwrap();
+        assert_eq!(
+            FullDevice(device).to_string(),
+            "NVIDIA Corporation AD102 [GeForce RTX 4090]"
+        );
+        assert_eq!(PrettyDevice(device).to_string(), "NVIDIA GeForce RTX 4090");
+    }
+
+    #[test]
+    fn name_from_db() {
+        let db =
+            PciIdDb::parse(&b"10de  NVIDIA Corporation\n\tffff  GB999 [GeForce RTX 9090]\n"[..]);
+        let device = |device_id| ResolvedDevice {
+            address: crate::pci::PciAddress {
+                domain: 0,
+                bus: 1,
+                device: 0,
+                function: 0,
+            },
+            vendor_id: 0x10de,
+            device_id,
+            class: crate::pci::PciClass {
+                class: 0x03,
+                subclass: 0x00,
+                prog_if: 0,
+            },
+            device: pci_ids::Device::from_vid_pid(0x10de, device_id),
+        };
+        let name = |device, db| {
+            PrettyName {
+                device: &device,
+                label: None,
+                db,
+            }
+            .to_string()
+        };
+        // Only the runtime database knows it
+        assert_eq!(name(device(0xffff), &db), "NVIDIA GeForce RTX 9090");
+        let builtin = PciIdDb::default();
+        assert_eq!(name(device(0xffff), &builtin), "10de:ffff");
+        // Falls back to the compiled-in names
+        assert_eq!(name(device(0x2684), &db), "NVIDIA GeForce RTX 4090");
+    }
+}
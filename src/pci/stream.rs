@@ -0,0 +1,87 @@
+//! Async wrappers for use inside a tokio runtime.
+//!
+//! Reading sysfs and procfs is blocking, so the enumeration runs on tokio's blocking thread pool
+//! to avoid stalling the runtime, the IO itself isn't asynchronous.
+use super::{PciAutoIter, PciBackendError, PciClass, PciDevIterBackend, ResolvedDevice};
+use futures_core::Stream;
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+
+/// Yields the devices found by [`PciAutoIter`] as they're read.
+pub struct PciStream {
+    rx: mpsc::Receiver<Result<ResolvedDevice, PciBackendError>>,
+}
+
+impl PciStream {
+    /// Starts enumerating in the background, must be called from within a tokio runtime.
+    pub fn resolved() -> Self {
+        let (tx, rx) = mpsc::channel(16);
+        tokio::task::spawn_blocking(move || {
+            let devices = match PciAutoIter::try_init() {
+                Ok(devices) => devices,
+                Err(err) => {
+                    let _ = tx.blocking_send(Err(err));
+                    return;
+                }
+            };
+            for device in devices.resolved() {
+                // The stream was dropped, stop reading
+                if tx.blocking_send(device).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { rx }
+    }
+}
+
+impl Stream for PciStream {
+    type Item = Result<ResolvedDevice, PciBackendError>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+/// Collects every GPU in the system. Devices that fail to read are skipped.
+pub async fn collect_gpus() -> Result<Vec<ResolvedDevice>, PciBackendError> {
+    tokio::task::spawn_blocking(|| {
+        Ok(PciAutoIter::try_init()?
+            .filter_class(PciClass::DISPLAY)
+            .resolved()
+            .flatten()
+            .collect())
+    })
+    .await
+    .map_err(io::Error::other)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::poll_fn;
+
+    #[test]
+    fn stream_matches_blocking() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let Ok(devices) = PciAutoIter::try_init() else {
+            return;
+        };
+        let expected = devices.resolved().flatten().count();
+        let streamed = runtime.block_on(async {
+            let mut stream = PciStream::resolved();
+            let mut count = 0;
+            while let Some(device) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+                count += device.is_ok() as usize;
+            }
+            count
+        });
+        assert_eq!(streamed, expected);
+        assert!(runtime.block_on(collect_gpus()).unwrap().len() <= expected);
+    }
+}
@@ -0,0 +1,11 @@
+//! Re-exports of the commonly used traits and types.
+//!
+//! ```
+//! use rxfetch::prelude::*;
+//! ```
+pub use crate::{
+    arrayvec::ArrayVec,
+    components::name::{current_uid, machine_id, OwnedSystemName, PwuId, SystemName},
+    display::{DisplayBytes, DisplayWide},
+    pci::{PciAutoIter, PciDevIterBackend},
+};
@@ -0,0 +1,265 @@
+//! Output rendered around the fetch information.
+use crate::display::{ColorMode, Styled};
+use std::fmt;
+
+const NORMAL: [&str; 8] = ["40", "41", "42", "43", "44", "45", "46", "47"];
+const BRIGHT: [&str; 8] = ["100", "101", "102", "103", "104", "105", "106", "107"];
+
+/// Nerd Font glyphs for the built-in components, keyed by label, see [`icon`].
+const ICONS: &[(&str, &str)] = &[
+    ("User", "\u{f007}"),
+    ("Kernel", "\u{f17c}"),
+    ("Uptime", "\u{f017}"),
+    ("Booted", "\u{f011}"),
+    ("Init", "\u{f085}"),
+    ("Session", "\u{f108}"),
+    ("Display Manager", "\u{f023}"),
+    ("CPU", "\u{f2db}"),
+    ("GPU", "\u{f08ae}"),
+    ("GPU Clock", "\u{f0e7}"),
+    ("Memory", "\u{f035b}"),
+    ("Disk (/)", "\u{f0a0}"),
+    ("Modules", "\u{f1b3}"),
+    ("Entropy", "\u{f074}"),
+    ("Virtualization", "\u{f233}"),
+    ("Cache", "\u{f1c0}"),
+    ("Tunables", "\u{f1de}"),
+    ("Terminal Font", "\u{f031}"),
+    ("Processes", "\u{f0ae}"),
+    ("Sound", "\u{f028}"),
+    ("Firmware", "\u{f0ad}"),
+    ("Secure Boot", "\u{f132}"),
+    ("Local IP", "\u{f0ac}"),
+];
+
+/// Built-in logos, keyed by name, see [`logo`].
+const LOGOS: &[(&str, &str)] = &[
+    (
+        "linux",
+        r"    .--.
+   |o_o |
+   |:_/ |
+  //   \ \
+ (|     | )
+/'\_   _/`\
+\___)=(___/",
+    ),
+    (
+        "arch",
+        r"      /\
+     /  \
+    /\   \
+   /      \
+  /   ,,   \
+ /   |  |  -\
+/_-''    ''-_\",
+    ),
+    (
+        "debian",
+        r"  _____
+ /  __ \
+|  /    |
+|  \___-
+-_
+  --_",
+    ),
+];
+
+/// The built-in logo called `name`, matched ignoring case, for [`crate::fetch::Fetch::logo`].
+pub fn logo(name: &str) -> Option<&'static str> {
+    LOGOS
+        .iter()
+        .find(|(logo, _)| logo.eq_ignore_ascii_case(name))
+        .map(|&(_, art)| art)
+}
+
+/// The Nerd Font glyph shown instead of the component `label` when icons are enabled,
+/// e.g. `\u{f17c}` (the Linux penguin) for `Kernel`. Matched ignoring case.
+///
+/// Every glyph takes a single cell, so alignment is unaffected.
+/// `None` for components without one, which keep their label.
+pub fn icon(label: &str) -> Option<&'static str> {
+    ICONS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(label))
+        .map(|&(_, glyph)| glyph)
+}
+
+/// The 8 normal and 8 bright terminal colors as two rows of swatches, see [`palette`].
+#[derive(Debug, Clone, Copy)]
+pub struct Palette<'b> {
+    block: &'b str,
+    mode: ColorMode,
+}
+
+/// Swatches of the terminal's color palette, so the theme can be checked at a glance.
+///
+/// Renders nothing when `mode` disables color, since the swatches would be invisible.
+pub fn palette(mode: ColorMode) -> Palette<'static> {
+    Palette { block: "  ", mode }
+}
+
+impl<'b> Palette<'b> {
+    /// Uses `block` for every swatch instead of two spaces.
+    pub fn with_block<'c>(self, block: &'c str) -> Palette<'c> {
+        Palette {
+            block,
+            mode: self.mode,
+        }
+    }
+}
+
+impl fmt::Display for Palette<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.mode.enabled() {
+            return Ok(());
+        }
+        for (idx, row) in [NORMAL, BRIGHT].into_iter().enumerate() {
+            if idx != 0 {
+                f.write_str("\n")?;
+            }
+            for sgr in row {
+                write!(
+                    f,
+                    "{}",
+                    Styled::new(self.block, Some(sgr), ColorMode::Always)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A `[████░░░░]` bar showing a fraction, see [`bar`].
+#[derive(Debug, Clone, Copy)]
+pub struct Bar {
+    fraction: f32,
+    width: usize,
+    mode: ColorMode,
+    fill: char,
+    empty: char,
+}
+
+/// A bar `width` cells wide, filled to `fraction`, which is clamped to `0.0..=1.0`.
+///
+/// The fill is green, then yellow from 60% and red from 85%, when color is enabled.
+pub fn bar(fraction: f32, width: usize) -> Bar {
+    Bar {
+        // NaN counts as empty, clamp would pass it through
+        fraction: if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        },
+        width,
+        mode: ColorMode::Auto,
+        fill: '█',
+        empty: '░',
+    }
+}
+
+impl Bar {
+    pub fn color(self, mode: ColorMode) -> Self {
+        Self { mode, ..self }
+    }
+    /// Uses `fill` and `empty` for the cells, e.g. `#` and `-` on terminals without Unicode.
+    pub fn glyphs(self, fill: char, empty: char) -> Self {
+        Self {
+            fill,
+            empty,
+            ..self
+        }
+    }
+    fn sgr(&self) -> &'static str {
+        match self.fraction {
+            f if f >= 0.85 => "31",
+            f if f >= 0.6 => "33",
+            _ => "32",
+        }
+    }
+}
+
+impl fmt::Display for Bar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let filled = (self.fraction * self.width as f32).round() as usize;
+        let fill = Repeat(self.fill, filled);
+        write!(
+            f,
+            "[{}{}]",
+            Styled::new(fill, Some(self.sgr()), self.mode),
+            Repeat(self.empty, self.width - filled)
+        )
+    }
+}
+
+/// A character repeated a number of times.
+#[derive(Debug, Clone, Copy)]
+struct Repeat(char, usize);
+
+impl fmt::Display for Repeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (0..self.1).try_for_each(|_| fmt::Write::write_char(f, self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swatches() {
+        let rendered = palette(ColorMode::Always).to_string();
+        let (normal, bright) = rendered.split_once('\n').unwrap();
+        assert!(normal.starts_with("\x1b[40m  \x1b[0m\x1b[41m  \x1b[0m"));
+        assert_eq!(crate::display::visible_width(normal), 16);
+        assert!(bright.ends_with("\x1b[107m  \x1b[0m"));
+        assert_eq!(palette(ColorMode::Never).to_string(), "");
+        let blocks = palette(ColorMode::Always).with_block("█").to_string();
+        assert_eq!(crate::display::visible_width(&blocks), 17);
+    }
+
+    #[test]
+    fn icons() {
+        assert_eq!(icon("Kernel"), Some("\u{f17c}"));
+        assert_eq!(icon("disk (/)"), Some("\u{f0a0}"));
+        assert_eq!(icon("Weather"), None);
+        for (label, glyph) in ICONS {
+            assert_eq!(crate::display::visible_width(glyph), 1, "{label}");
+        }
+        // Every built-in component has one
+        for entry in crate::components::REGISTRY {
+            let label = (entry.new)().label().to_string();
+            assert!(icon(&label).is_some(), "{label}");
+        }
+    }
+
+    #[test]
+    fn logos() {
+        assert!(logo("Arch").unwrap().starts_with("      /\\\n"));
+        assert_eq!(logo("linux").unwrap().lines().count(), 7);
+        assert_eq!(logo("windows"), None);
+    }
+
+    #[test]
+    fn bars() {
+        let plain = |fraction| bar(fraction, 8).color(ColorMode::Never).to_string();
+        assert_eq!(plain(0.5), "[████░░░░]");
+        assert_eq!(plain(-1.0), "[░░░░░░░░]");
+        assert_eq!(plain(2.0), "[████████]");
+        assert_eq!(plain(f32::NAN), "[░░░░░░░░]");
+        assert_eq!(
+            bar(0.25, 4)
+                .glyphs('#', '-')
+                .color(ColorMode::Never)
+                .to_string(),
+            "[#---]"
+        );
+        assert_eq!(
+            bar(0.9, 2).color(ColorMode::Always).to_string(),
+            "[\x1b[31m██\x1b[0m]"
+        );
+        let yellow = bar(0.62, 5).color(ColorMode::Always).to_string();
+        assert!(yellow.starts_with("[\x1b[33m███"));
+        assert_eq!(crate::display::visible_width(&yellow), 7);
+    }
+}
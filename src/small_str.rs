@@ -0,0 +1,79 @@
+//! A fixed-capacity string stored inline.
+use crate::arrayvec::{ArrayVec, CapacityError};
+use std::fmt;
+
+/// A UTF-8 string of at most `CAP` bytes that never allocates.
+///
+/// Implements [`fmt::Write`], so `write!` can target it directly.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SmallStr<const CAP: usize>(ArrayVec<u8, CAP>);
+
+impl<const CAP: usize> SmallStr<CAP> {
+    pub const fn new() -> Self {
+        Self(ArrayVec::new())
+    }
+    /// Appends `s`, leaving the string unchanged if it doesn't fit.
+    pub fn push_str(&mut self, s: &str) -> Result<(), CapacityError> {
+        if s.len() > self.0.remaining_capacity() {
+            return Err(CapacityError);
+        }
+        self.0.copy_from_slice(s.as_bytes());
+        Ok(())
+    }
+    pub fn as_str(&self) -> &str {
+        // SAFETY: only whole `&str`s are ever appended
+        unsafe { std::str::from_utf8_unchecked(&self.0) }
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+}
+
+impl<const CAP: usize> TryFrom<&str> for SmallStr<CAP> {
+    type Error = CapacityError;
+    fn try_from(s: &str) -> Result<Self, CapacityError> {
+        let mut small = Self::new();
+        small.push_str(s)?;
+        Ok(small)
+    }
+}
+
+impl<const CAP: usize> fmt::Write for SmallStr<CAP> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const CAP: usize> fmt::Display for SmallStr<CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0.as_str_lossy(), f)
+    }
+}
+
+impl<const CAP: usize> fmt::Debug for SmallStr<CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn write_and_overflow() {
+        let mut label = SmallStr::<8>::try_from("GPU").unwrap();
+        write!(label, ":{}", 12).unwrap();
+        assert_eq!(label.to_string(), "GPU:12");
+        assert!(write!(label, "345").is_err());
+        assert_eq!(label.as_str(), "GPU:12");
+        assert_eq!(SmallStr::<2>::try_from("GPU:"), Err(CapacityError));
+    }
+}
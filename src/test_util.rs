@@ -0,0 +1,52 @@
+//! Helpers shared by the unit tests.
+use std::{
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// A fresh directory for fixtures under the system temp dir, removed along with its contents
+/// when dropped, so a failing test doesn't leave it behind for the next run.
+#[derive(Debug)]
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates `rxfetch-<name>-<pid>`, replacing whatever an earlier run left there.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("rxfetch-{name}-{}", std::process::id()));
+        match fs::remove_dir_all(&path) {
+            Err(err) if err.kind() == std::io::ErrorKind::NotADirectory => {
+                fs::remove_file(&path).unwrap()
+            }
+            _ => {}
+        }
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl From<&TempDir> for PathBuf {
+    fn from(dir: &TempDir) -> Self {
+        dir.0.clone()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        // Tests may have removed it already
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}